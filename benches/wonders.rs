@@ -0,0 +1,138 @@
+//! Benchmarks for the hot paths of the wonders routes.
+//!
+//! The `baseline` benchmarks replicate the approach used before the dataset was indexed (cloning
+//! a `Vec` of the whole dataset, filtering with `to_lowercase` allocations per wonder and
+//! serialising from scratch), for comparison with the current implementation.
+
+use axum::{body::Body, http::Request, Router};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tower::ServiceExt;
+use world_wonders_api::{
+    config::get_config,
+    data::{Category, Wonder, WONDERS},
+    routes::wonders::{
+        self, filter_wonders_ignore_empty, find_wonder_by_name, sort_wonders, wonder_response,
+        wonders_response, SortBy, WonderParamsFiltering, WonderParamsSorting,
+    },
+    versioning::ApiVersion,
+};
+
+// BASELINE ---------------------------------------------------------------------------------------
+fn baseline_filter(wonders: &mut Vec<&'static Wonder>, params: &WonderParamsFiltering) {
+    if let Some(name) = params.name.as_deref() {
+        wonders.retain(|w| w.name.to_lowercase().contains(&name.to_lowercase()));
+    };
+    if let Some(location) = params.location.as_deref() {
+        wonders.retain(|w| w.location.to_lowercase().contains(&location.to_lowercase()));
+    };
+    if let Some(category) = params.category.as_ref() {
+        wonders.retain(|w| w.categories.contains(category));
+    };
+}
+
+fn baseline(params: &WonderParamsFiltering, sort_by: Option<SortBy>) -> Vec<u8> {
+    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
+    baseline_filter(&mut wonders, params);
+    if let Some(SortBy::BuildYear) = sort_by {
+        wonders.sort_by_key(|w| w.build_year);
+    }
+    serde_json::to_vec(&wonders).unwrap()
+}
+
+fn baseline_by_name(name: &str) -> Vec<u8> {
+    let wonder = WONDERS
+        .iter()
+        .find(|w| w.name.to_ascii_lowercase().replace(' ', "-") == name)
+        .unwrap();
+    serde_json::to_vec(wonder).unwrap()
+}
+
+// CASES ------------------------------------------------------------------------------------------
+fn cases() -> Vec<(&'static str, WonderParamsFiltering, Option<SortBy>)> {
+    vec![
+        ("all", WonderParamsFiltering::default(), None),
+        (
+            "name",
+            WonderParamsFiltering {
+                name: Some("temple".to_string()),
+                ..Default::default()
+            },
+            None,
+        ),
+        (
+            "category_sorted",
+            WonderParamsFiltering {
+                category: Some(Category::SevenWonders),
+                ..Default::default()
+            },
+            Some(SortBy::BuildYear),
+        ),
+    ]
+}
+
+fn bench_list(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list");
+
+    for (name, params, sort_by) in cases() {
+        group.bench_with_input(BenchmarkId::new("baseline", name), &params, |b, params| {
+            b.iter(|| baseline(black_box(params), sort_by))
+        });
+        group.bench_with_input(BenchmarkId::new("indexed", name), &params, |b, params| {
+            b.iter(|| {
+                let mut ids = filter_wonders_ignore_empty(WonderParamsFiltering {
+                    name: params.name.clone(),
+                    category: params.category,
+                    ..Default::default()
+                })
+                .unwrap();
+                sort_wonders(
+                    &mut ids,
+                    WonderParamsSorting {
+                        sort_by,
+                        sort_reverse: None,
+                    },
+                );
+                wonders_response(black_box(&ids))
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_by_name(c: &mut Criterion) {
+    let mut group = c.benchmark_group("by_name");
+    let name = "statue-of-liberty";
+
+    group.bench_function("baseline", |b| b.iter(|| baseline_by_name(black_box(name))));
+    group.bench_function("indexed", |b| {
+        b.iter(|| wonder_response(find_wonder_by_name(black_box(name)).unwrap()))
+    });
+
+    group.finish();
+}
+
+fn bench_router(c: &mut Criterion) {
+    let config = get_config().expect("Failed to read configuration");
+    let router: Router = wonders::routes(&config.cache, ApiVersion::V0).into();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("router");
+    for uri in [
+        "/",
+        "/?name=temple",
+        "/?category=SevenWonders&sort_by=BuildYear",
+    ] {
+        group.bench_function(uri, |b| {
+            b.to_async(&runtime).iter(|| {
+                router
+                    .clone()
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_list, bench_by_name, bench_router);
+criterion_main!(benches);
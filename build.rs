@@ -0,0 +1,65 @@
+use std::{
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use a vendored `protoc` so that building doesn't depend on it being installed on the system
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::compile_protos("proto/wonders.proto")?;
+
+    // Time the dataset was last changed, used for `Last-Modified` headers. Taken from
+    // `SOURCE_DATE_EPOCH` for reproducible builds, otherwise from the last commit touching
+    // `data.json`, as file modification times change on every checkout
+    println!("cargo:rerun-if-changed=data.json");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    if std::path::Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs/heads");
+    }
+    let last_modified = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.parse()?,
+        Err(_) => match git(&["log", "-1", "--format=%ct", "--", "data.json"])
+            .and_then(|time| time.parse().ok())
+        {
+            Some(time) => time,
+            None => {
+                println!(
+                    "cargo:warning=Using the modification time of `data.json` for \
+                     `Last-Modified` headers, set `SOURCE_DATE_EPOCH` to make it stable"
+                );
+                std::fs::metadata("data.json")?
+                    .modified()
+                    .unwrap_or_else(|_| SystemTime::now())
+                    .duration_since(UNIX_EPOCH)?
+                    .as_secs()
+            }
+        },
+    };
+    println!("cargo:rustc-env=DATASET_LAST_MODIFIED={last_modified}");
+
+    // Commit the API was built from, reported by the health endpoints. Can be provided using
+    // `GIT_COMMIT` when building without the repository, e.g. in Docker
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    let git_commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .or_else(|| git(&["rev-parse", "HEAD"]));
+    if let Some(git_commit) = git_commit {
+        println!("cargo:rustc-env=GIT_COMMIT={git_commit}");
+    }
+
+    Ok(())
+}
+
+/// Run a git command in the repository, returning its trimmed output if successful
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+        .filter(|output| !output.is_empty())
+}
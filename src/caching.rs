@@ -0,0 +1,180 @@
+use std::time::SystemTime;
+
+use axum::{
+    extract::{OriginalUri, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::CacheConfig,
+    data::{DATASET_LAST_MODIFIED, DATASET_VERSION},
+};
+
+/// Headers added to cacheable responses, used as the state for [`conditional_get`]
+#[derive(Clone)]
+pub struct CacheHeaders {
+    cache_control: HeaderValue,
+    last_modified: HeaderValue,
+}
+impl CacheHeaders {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            cache_control: HeaderValue::from_str(&config.cache_control)
+                .expect("Invalid `cache.cache_control` configuration value"),
+            last_modified: HeaderValue::from_str(&httpdate::fmt_http_date(*DATASET_LAST_MODIFIED))
+                .expect("Failed to format `Last-Modified` header"),
+        }
+    }
+}
+
+/// Middleware adding `ETag`, `Last-Modified` and `Cache-Control` headers to successful
+/// responses, and replacing them with `304 Not Modified` for matching conditional requests.
+///
+/// Only intended for routes whose responses are fully determined by the dataset and the request
+/// URI. The handler still runs for conditional requests, so errors are returned as usual.
+pub async fn conditional_get(
+    State(cache_headers): State<CacheHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return next.run(req).await;
+    }
+
+    // Use the original URI, as the path is stripped of any prefixes when nested
+    let uri = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| req.uri(), |OriginalUri(uri)| uri);
+    // Responses are compressed based on `Accept-Encoding`, and different encodings of a response
+    // need different strong tags
+    let accept_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .map_or(&[][..], HeaderValue::as_bytes);
+    let etag = etag_for(
+        uri.path_and_query().map_or("", |p| p.as_str()),
+        accept_encoding,
+    );
+
+    let mut headers = HeaderMap::with_capacity(3);
+    headers.insert(header::ETAG, etag.clone());
+    headers.insert(header::CACHE_CONTROL, cache_headers.cache_control.clone());
+    headers.insert(header::LAST_MODIFIED, cache_headers.last_modified.clone());
+
+    let not_modified = is_not_modified(req.headers(), &etag);
+    let mut response = next.run(req).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+    response.headers_mut().extend(headers);
+    response
+}
+
+/// Compute a strong `ETag` from the dataset version, the given path and query, and the accepted
+/// encodings
+fn etag_for(path_and_query: &str, accept_encoding: &[u8]) -> HeaderValue {
+    let mut hasher = Sha256::new();
+    hasher.update(DATASET_VERSION.as_bytes());
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(accept_encoding);
+    let hash = format!("{:x}", hasher.finalize());
+
+    HeaderValue::from_str(&format!("\"{}\"", &hash[..32])).expect("ETag is always valid ASCII")
+}
+
+/// Evaluate the conditional request headers. As per RFC 9110, `If-Modified-Since` is ignored
+/// when `If-None-Match` is present
+fn is_not_modified(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        let etag = etag.to_str().unwrap_or_default();
+
+        // Weak comparison, as is required for `If-None-Match`
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag);
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| httpdate::parse_http_date(v).ok())
+        .is_some_and(|since: SystemTime| {
+            // HTTP dates only have a precision of seconds
+            let last_modified =
+                httpdate::parse_http_date(&httpdate::fmt_http_date(*DATASET_LAST_MODIFIED))
+                    .unwrap_or(*DATASET_LAST_MODIFIED);
+            last_modified <= since
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_for() {
+        let etag = etag_for("/v0/wonders?category=SevenWonders", b"");
+        assert_eq!(etag, etag_for("/v0/wonders?category=SevenWonders", b""));
+        assert_ne!(etag, etag_for("/v0/wonders?category=Civ5", b""));
+        assert_ne!(etag, etag_for("/v0/wonders?category=SevenWonders", b"gzip"));
+
+        let etag = etag.to_str().unwrap();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+    }
+
+    #[test]
+    fn test_is_not_modified() {
+        let etag = etag_for("/", b"");
+        let header = |name, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+            headers
+        };
+
+        assert!(!is_not_modified(&HeaderMap::new(), &etag));
+
+        // If-None-Match
+        let tag = etag.to_str().unwrap();
+        assert!(is_not_modified(&header(header::IF_NONE_MATCH, tag), &etag));
+        assert!(is_not_modified(
+            &header(header::IF_NONE_MATCH, &format!("\"abc\", W/{tag}")),
+            &etag
+        ));
+        assert!(is_not_modified(&header(header::IF_NONE_MATCH, "*"), &etag));
+        assert!(!is_not_modified(
+            &header(header::IF_NONE_MATCH, "\"abc\""),
+            &etag
+        ));
+
+        // If-Modified-Since
+        let last_modified = httpdate::fmt_http_date(*DATASET_LAST_MODIFIED);
+        assert!(is_not_modified(
+            &header(header::IF_MODIFIED_SINCE, &last_modified),
+            &etag
+        ));
+        assert!(!is_not_modified(
+            &header(header::IF_MODIFIED_SINCE, "Thu, 01 Jan 1970 00:00:00 GMT"),
+            &etag
+        ));
+
+        // If-None-Match takes precedence
+        let mut headers = header(header::IF_NONE_MATCH, "\"abc\"");
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&last_modified).unwrap(),
+        );
+        assert!(!is_not_modified(&headers, &etag));
+    }
+}
@@ -1,48 +1,193 @@
-use std::str::FromStr;
+use std::{
+    num::{NonZeroU32, NonZeroU64},
+    str::FromStr,
+};
 
-use serde::{de::Error, Deserialize, Deserializer};
 use strum_macros::{Display, EnumString};
 
 #[derive(serde::Deserialize)]
 pub struct Config {
     pub network: NetworkConfig,
+    pub grpc: GrpcConfig,
+    pub cache: CacheConfig,
+    pub compression: CompressionConfig,
+    pub versioning: VersioningConfig,
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub api_keys: ApiKeysConfig,
+    pub cors: CorsConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub logging: LoggingConfig,
 }
 
 #[derive(serde::Deserialize)]
 pub struct NetworkConfig {
-    #[serde(deserialize_with = "deserialize_host")]
-    pub host: [u8; 4],
+    /// IPv4 or IPv6 address, or hostname, to bind
+    pub host: String,
     pub port: u16,
+    /// Additional addresses to bind, e.g. `[::1]:8138` or `localhost:8080`
+    #[serde(default)]
+    pub binds: Vec<String>,
+    /// Unix domain socket to listen on, in addition to the above addresses
+    pub unix_socket: Option<UnixSocketConfig>,
+    /// Serves HTTPS instead of plain HTTP on the above addresses when provided
+    pub tls: Option<TlsConfig>,
+}
+impl NetworkConfig {
+    /// Address for binding the given port on the configured host, which may be an IPv6 address in
+    /// square brackets
+    pub fn host_addr(&self, port: u16) -> (&str, u16) {
+        let host = self
+            .host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(&self.host);
+        (host, port)
+    }
 }
 
-/// Custom de-serialiser for the host, converting a string value to `[u8; 4]`
-fn deserialize_host<'de, D>(deserializer: D) -> Result<[u8; 4], D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s: String = Deserialize::deserialize(deserializer)?;
-    let split: Vec<&str> = s.split('.').collect();
+#[derive(serde::Deserialize)]
+pub struct UnixSocketConfig {
+    pub path: String,
+    /// Permissions for the socket file, e.g. `0o660`
+    pub mode: Option<u32>,
+}
 
-    if split.len() != 4 {
-        return Err(D::Error::custom(
-            "Invalid host -> value needs to be provided in the format '0.0.0.0', with 4 period-separated numbers between 0 and 255.",
-        ));
-    }
+#[derive(serde::Deserialize, Clone)]
+pub struct TlsConfig {
+    /// PEM file containing the certificate chain, which is reloaded when changed
+    pub cert_path: String,
+    /// PEM file containing the private key, which is reloaded when changed
+    pub key_path: String,
+    /// Port to serve plain HTTP on, redirecting all requests to HTTPS
+    pub redirect_http_port: Option<u16>,
+}
 
-    let mut res: [u8; 4] = [0, 0, 0, 0];
-    for (i, s) in split.into_iter().enumerate() {
-        res[i] = match s.parse::<u8>() {
-            Ok(n) => n,
-            Err(e) => {
-                return Err(D::Error::custom(format!(
-                    "Invalid host -> error parsing one of the period-separated numbers for the host - ensure all values are within 0-255.\n\
-                    Error encountered: {e}"
-                )))
-            }
-        };
-    }
+#[derive(serde::Deserialize)]
+pub struct GrpcConfig {
+    /// Whether the gRPC server should be started alongside the HTTP server
+    pub enabled: bool,
+    /// Port for the gRPC server, which binds to the same host as the HTTP server
+    pub port: u16,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CacheConfig {
+    /// `Cache-Control` header value for responses which only change along with the dataset
+    pub cache_control: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CompressionConfig {
+    pub gzip: bool,
+    pub br: bool,
+    pub zstd: bool,
+    /// Minimum response body size, in bytes, for compression to be applied
+    pub min_size: u16,
+    /// Compression level passed to the algorithms. Uses each algorithm's default if not provided
+    pub level: Option<i32>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct VersioningConfig {
+    /// Date (HTTP-date format) from which `/v0` is deprecated, advertised using the `Deprecation`
+    /// header
+    pub v0_deprecation: Option<String>,
+    /// Date (HTTP-date format) after which `/v0` may be removed, advertised using the `Sunset`
+    /// header
+    pub v0_sunset: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RateLimitConfig {
+    /// Number of requests each client can make at once before being limited
+    pub burst_size: NonZeroU32,
+    /// Interval, in milliseconds, after which each client is allowed another request
+    pub per_millisecond: NonZeroU64,
+    /// Overrides for specific routes
+    #[serde(default)]
+    pub routes: Vec<RouteRateLimitConfig>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RouteRateLimitConfig {
+    /// Applies to routes starting with this prefix, with the longest matching prefix being used.
+    /// Only matches whole path segments, so `/v0/wonders` doesn't match `/v0/wonders-list`
+    pub prefix: String,
+    pub burst_size: NonZeroU32,
+    pub per_millisecond: NonZeroU64,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct ApiKeysConfig {
+    /// Keys defined directly in the configuration
+    #[serde(default)]
+    pub keys: Vec<ApiKeyConfig>,
+    /// TOML file defining additional keys, as a `keys` array in the same format
+    pub file: Option<String>,
+    /// JSON file which the daily usage of each key is saved to, so quotas persist across restarts.
+    /// Usage is only tracked in memory if not provided
+    pub usage_file: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ApiKeyConfig {
+    /// Identifies the key in logs and saved usage, without exposing the key itself
+    pub name: String,
+    pub key: String,
+    pub burst_size: NonZeroU32,
+    pub per_millisecond: NonZeroU64,
+    /// Maximum number of requests per day (UTC) - unlimited if not provided
+    pub daily_quota: Option<u64>,
+}
 
-    Ok(res)
+#[derive(serde::Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to make cross-origin requests, or `["*"]` to allow any origin
+    pub allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests, or `["*"]` to allow any method
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests, or `["*"]` to allow any header
+    pub allowed_headers: Vec<String>,
+    /// How long, in seconds, browsers may cache preflight responses
+    pub max_age: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct SecurityHeadersConfig {
+    /// `Strict-Transport-Security` header - should only be set when served over HTTPS
+    pub hsts: Option<String>,
+    /// Whether to send `X-Content-Type-Options: nosniff`
+    pub content_type_options: bool,
+    /// `Referrer-Policy` header
+    pub referrer_policy: Option<String>,
+    /// `Content-Security-Policy` header, which needs to allow the docs and GraphQL playground pages
+    pub content_security_policy: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Filter directives in the same format as the `RUST_LOG` environment variable, which takes
+    /// priority when set
+    pub level: String,
+    /// OTLP (gRPC) endpoint to export request spans to, e.g. `http://localhost:4317`
+    pub otlp_endpoint: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    Pretty,
+    /// One JSON object per line, including the fields of the current spans
+    Json,
+}
+
+/// Format of the file given by `api_keys.file`
+#[derive(serde::Deserialize)]
+struct ApiKeysFile {
+    keys: Vec<ApiKeyConfig>,
 }
 
 #[derive(Display, EnumString)]
@@ -63,7 +208,7 @@ pub fn get_config() -> Result<Config, config::ConfigError> {
         .map(|e| Environment::from_str(&e).expect("Failed to parse `APP_ENV`"))
         .unwrap_or(Environment::Dev);
 
-    config::Config::builder()
+    let mut config = config::Config::builder()
         // Config files
         .add_source(config::File::from(config_dir.join("base.toml")))
         .add_source(config::File::from(config_dir.join(format!("{}.toml", env))))
@@ -74,7 +219,46 @@ pub fn get_config() -> Result<Config, config::ConfigError> {
                 .separator("__"),
         )
         .build()?
-        .try_deserialize::<Config>()
+        .try_deserialize::<Config>()?;
+
+    if let Some(file) = &config.api_keys.file {
+        let keys = read_api_keys_file(file)?;
+        config.api_keys.keys.extend(keys);
+    }
+    validate_api_keys(&config.api_keys.keys)?;
+
+    Ok(config)
+}
+
+/// Read the API keys defined in the given file
+fn read_api_keys_file(file: &str) -> Result<Vec<ApiKeyConfig>, config::ConfigError> {
+    Ok(config::Config::builder()
+        .add_source(config::File::new(file, config::FileFormat::Toml))
+        .build()?
+        .try_deserialize::<ApiKeysFile>()?
+        .keys)
+}
+
+/// Ensure API keys and their names are unique
+fn validate_api_keys(keys: &[ApiKeyConfig]) -> Result<(), config::ConfigError> {
+    let mut names = std::collections::HashSet::new();
+    let mut values = std::collections::HashSet::new();
+    for key in keys {
+        if !names.insert(&key.name) {
+            return Err(config::ConfigError::Message(format!(
+                "Duplicate API key name '{}'",
+                key.name
+            )));
+        }
+        if !values.insert(&key.key) {
+            return Err(config::ConfigError::Message(format!(
+                "API key '{}' has the same key as another",
+                key.name
+            )));
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -86,4 +270,48 @@ mod tests {
     fn test_get_config() {
         assert!(get_config().is_ok());
     }
+
+    #[test]
+    fn test_read_api_keys_file() {
+        let file = std::env::temp_dir().join(format!(
+            "world-wonders-api-keys-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &file,
+            r#"
+            [[keys]]
+            name = "batch"
+            key = "abc"
+            burst_size = 100
+            per_millisecond = 10
+            daily_quota = 100000
+
+            [[keys]]
+            name = "internal"
+            key = "def"
+            burst_size = 1000
+            per_millisecond = 1
+            "#,
+        )
+        .unwrap();
+
+        let keys = read_api_keys_file(file.to_str().unwrap()).unwrap();
+        std::fs::remove_file(file).unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].name, "batch");
+        assert_eq!(keys[0].daily_quota, Some(100_000));
+        assert_eq!(keys[1].burst_size.get(), 1000);
+        assert_eq!(keys[1].daily_quota, None);
+        assert!(validate_api_keys(&keys).is_ok());
+
+        let mut keys = keys;
+        keys.push(ApiKeyConfig {
+            name: "batch".to_string(),
+            key: "ghi".to_string(),
+            ..keys[1]
+        });
+        assert!(validate_api_keys(&keys).is_err());
+    }
 }
@@ -1,12 +1,38 @@
+use async_graphql::{Enum, SimpleObject};
+use axum::body::Bytes;
 use garde::Validate;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use strum_macros::EnumIter;
 
-use std::sync::LazyLock;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const DATA: &str = include_str!("../data.json");
 
 /// Human history time period of a world wonder
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, EnumIter, JsonSchema)]
+#[derive(
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    JsonSchema,
+    Enum,
+)]
 pub enum TimePeriod {
     Prehistoric,
     Ancient,
@@ -30,7 +56,7 @@ impl From<i16> for TimePeriod {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Validate)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Validate, SimpleObject)]
 pub struct Links {
     #[garde(url, prefix("https://en.wikipedia.org/wiki/"))]
     pub wiki: String,
@@ -45,7 +71,19 @@ pub struct Links {
 }
 
 #[derive(
-    Clone, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter, JsonSchema,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    JsonSchema,
+    Enum,
 )]
 pub enum Category {
     /// Wonder is one of the "7 Wonders of the Ancient World".
@@ -60,7 +98,169 @@ pub enum Category {
     Civ6,
 }
 
-#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Validate)]
+/// Continent a world wonder is located in, which is the last part of its location
+#[derive(
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    JsonSchema,
+    Enum,
+)]
+pub enum Continent {
+    Africa,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+impl Continent {
+    /// Parse the continent from a location such as "Giza, Egypt, Africa"
+    pub fn from_location(location: &str) -> Option<Self> {
+        let (_, continent) = location.rsplit_once(',')?;
+        match continent.trim() {
+            "Africa" => Some(Self::Africa),
+            "Asia" => Some(Self::Asia),
+            "Europe" => Some(Self::Europe),
+            "North America" => Some(Self::NorthAmerica),
+            "Oceania" => Some(Self::Oceania),
+            "South America" => Some(Self::SouthAmerica),
+            _ => None,
+        }
+    }
+}
+
+/// Location of a world wonder on the globe
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+impl Coordinates {
+    /// Mean radius of the Earth, in kilometres
+    const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+    /// Parse the coordinates of the place marked in a Google Maps link, which are given by
+    /// `!3d<latitude>!4d<longitude>`
+    pub fn from_google_maps(link: &str) -> Option<Self> {
+        let (_, rest) = link.split_once("!3d")?;
+        let (latitude, rest) = rest.split_once("!4d")?;
+        let longitude = rest.split('!').next()?;
+
+        let coordinates = Self {
+            latitude: latitude.parse().ok()?,
+            longitude: longitude.parse().ok()?,
+        };
+        ((-90.0..=90.0).contains(&coordinates.latitude)
+            && (-180.0..=180.0).contains(&coordinates.longitude))
+        .then_some(coordinates)
+    }
+
+    /// Great-circle distance to other coordinates in kilometres, using the haversine formula
+    pub fn distance_km(&self, other: &Self) -> f64 {
+        let (lat_a, lat_b) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat_b - lat_a;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let h =
+            (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS_KM * h.sqrt().asin()
+    }
+}
+
+/// TF-IDF weights of the terms in a text, normalised to unit length and sorted by term
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TermVector(Vec<(String, f64)>);
+impl TermVector {
+    /// Common words which say little about a text
+    const STOP_WORDS: [&'static str; 32] = [
+        "about", "after", "also", "and", "are", "around", "been", "but", "during", "for", "where",
+        "from", "had", "has", "have", "its", "into", "it's", "most", "not", "one", "over", "than",
+        "that", "the", "their", "this", "was", "were", "which", "who", "with",
+    ];
+
+    /// Vectors for each of the given texts, with terms weighted by how rare they are across them
+    pub fn from_texts(texts: &[&str]) -> Vec<Self> {
+        let term_counts: Vec<HashMap<String, usize>> = texts
+            .iter()
+            .map(|text| {
+                let mut counts = HashMap::new();
+                for term in Self::terms(text) {
+                    *counts.entry(term).or_default() += 1;
+                }
+                counts
+            })
+            .collect();
+
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for counts in &term_counts {
+            for term in counts.keys() {
+                *document_frequency.entry(term).or_default() += 1;
+            }
+        }
+
+        term_counts
+            .iter()
+            .map(|counts| {
+                let mut weights: Vec<(String, f64)> = counts
+                    .iter()
+                    .map(|(term, &count)| {
+                        let idf =
+                            (texts.len() as f64 / document_frequency[term.as_str()] as f64).ln();
+                        (term.clone(), count as f64 * idf)
+                    })
+                    .filter(|(_, weight)| *weight > 0.0)
+                    .collect();
+                weights.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+                let norm = weights.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+                for (_, weight) in &mut weights {
+                    *weight /= norm;
+                }
+                Self(weights)
+            })
+            .collect()
+    }
+
+    /// Lowercase words in the text, excluding short and common words
+    fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .map(|word| word.trim_matches('\'').to_lowercase())
+            .filter(|word| word.chars().count() >= 3 && !Self::STOP_WORDS.contains(&word.as_str()))
+    }
+
+    /// Cosine similarity with another vector, from 0 (no terms in common) to 1
+    pub fn similarity(&self, other: &Self) -> f64 {
+        let (mut a, mut b) = (self.0.iter().peekable(), other.0.iter().peekable());
+        let mut dot = 0.0;
+        while let (Some((term_a, weight_a)), Some((term_b, weight_b))) = (a.peek(), b.peek()) {
+            match term_a.cmp(term_b) {
+                std::cmp::Ordering::Less => {
+                    a.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    b.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    dot += weight_a * weight_b;
+                    a.next();
+                    b.next();
+                }
+            }
+        }
+        dot.min(1.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Validate, SimpleObject)]
 #[garde(allow_unvalidated)]
 pub struct Wonder {
     #[garde(length(min = 3, max = 150))]
@@ -79,15 +279,173 @@ pub struct Wonder {
     #[garde(dive)]
     pub links: Links,
     pub categories: Vec<Category>,
+    /// Editorial score of how well known a world wonder is, from 1 (obscure) to 10 (famous).
+    /// Used for weighting random selections
+    #[garde(range(min = 1, max = 10))]
+    pub popularity: u8,
 }
 
 /// All wonders, read from `data.json`
 pub static WONDERS: LazyLock<Vec<Wonder>> = LazyLock::new(|| {
-    serde_json::from_str(include_str!("../data.json"))
+    serde_json::from_str(DATA)
         .map_err(|e| panic!("Encountered error while parsing JSON into wonders vec: {e:?}"))
         .unwrap()
 });
 
+impl Wonder {
+    /// Identifier used for fetching a wonder by name - lowercase letters with spaces replaced
+    /// with '-'
+    pub fn slug(&self) -> String {
+        self.name.to_ascii_lowercase().replace(' ', "-")
+    }
+
+    /// Coordinates of the wonder, if it has a Google Maps link
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        self.links
+            .google_maps
+            .as_deref()
+            .and_then(Coordinates::from_google_maps)
+    }
+
+    /// Continent the wonder is located in. Every location in the dataset defines a continent
+    pub fn continent(&self) -> Continent {
+        Continent::from_location(&self.location)
+            .unwrap_or_else(|| panic!("Location must define a continent: {}", self.location))
+    }
+}
+
+/// Lookup tables for [`WONDERS`], built once so that requests don't need to repeat the same work.
+///
+/// Wonders are referred to by their index in [`WONDERS`].
+pub struct WonderIndex {
+    /// Lowercase names, for case-insensitive searching
+    pub names_lowercase: Vec<String>,
+    /// Lowercase locations, for case-insensitive searching
+    pub locations_lowercase: Vec<String>,
+    /// Wonders belonging to each category, in dataset order
+    pub by_category: HashMap<Category, Vec<usize>>,
+    /// Wonders belonging to each time period, in dataset order
+    pub by_time_period: HashMap<TimePeriod, Vec<usize>>,
+    /// Continent of each wonder
+    pub continents: Vec<Continent>,
+    /// Coordinates of each wonder, if known
+    pub coordinates: Vec<Option<Coordinates>>,
+    /// TF-IDF vector of each wonder's summary, for finding similar wonders
+    pub summary_terms: Vec<TermVector>,
+    /// Wonder for each slug (see [`Wonder::slug`])
+    pub by_slug: HashMap<String, usize>,
+    /// Wonders sorted by build year, with ties kept in dataset order
+    pub by_build_year: Vec<usize>,
+    /// Position of each wonder in [`Self::by_build_year`]
+    pub build_year_rank: Vec<usize>,
+    /// Position of each wonder when sorted alphabetically by name
+    pub alphabetical_rank: Vec<usize>,
+    /// Each wonder, pre-serialised to JSON
+    pub json: Vec<Bytes>,
+}
+impl WonderIndex {
+    pub fn new(wonders: &[Wonder]) -> Self {
+        let mut by_category: HashMap<Category, Vec<usize>> = HashMap::new();
+        let mut by_time_period: HashMap<TimePeriod, Vec<usize>> = HashMap::new();
+        for (i, wonder) in wonders.iter().enumerate() {
+            for category in &wonder.categories {
+                by_category.entry(*category).or_default().push(i);
+            }
+            by_time_period
+                .entry(wonder.time_period)
+                .or_default()
+                .push(i);
+        }
+
+        let mut by_build_year: Vec<usize> = (0..wonders.len()).collect();
+        by_build_year.sort_by_key(|&i| wonders[i].build_year);
+
+        let mut alphabetical: Vec<usize> = (0..wonders.len()).collect();
+        alphabetical.sort_by(|&a, &b| wonders[a].name.cmp(&wonders[b].name));
+
+        Self {
+            names_lowercase: wonders.iter().map(|w| w.name.to_lowercase()).collect(),
+            locations_lowercase: wonders.iter().map(|w| w.location.to_lowercase()).collect(),
+            by_category,
+            by_time_period,
+            continents: wonders.iter().map(Wonder::continent).collect(),
+            coordinates: wonders.iter().map(Wonder::coordinates).collect(),
+            summary_terms: TermVector::from_texts(
+                &wonders
+                    .iter()
+                    .map(|w| w.summary.as_str())
+                    .collect::<Vec<_>>(),
+            ),
+            by_slug: wonders
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (w.slug(), i))
+                .collect(),
+            build_year_rank: ranks(&by_build_year),
+            by_build_year,
+            alphabetical_rank: ranks(&alphabetical),
+            json: wonders
+                .iter()
+                .map(|w| {
+                    serde_json::to_vec(w)
+                        .map(Bytes::from)
+                        .expect("Failed to serialise wonder")
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Invert a sorted order of indexes, giving the position of each index within it
+fn ranks(order: &[usize]) -> Vec<usize> {
+    let mut ranks = vec![0; order.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank;
+    }
+    ranks
+}
+
+/// Lookup tables for all wonders
+pub static INDEX: LazyLock<WonderIndex> = LazyLock::new(|| WonderIndex::new(&WONDERS));
+
+/// Whether the dataset has been loaded by [`load`]
+static LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Load status of the dataset, as reported by the health endpoints
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetStatus {
+    Loading,
+    Loaded,
+}
+
+/// Parse and index the dataset, so that requests aren't slowed down by doing so lazily. The API
+/// is only reported as ready once this has finished
+pub fn load() {
+    LazyLock::force(&INDEX);
+    LOADED.store(true, Ordering::Release);
+}
+
+pub fn dataset_status() -> DatasetStatus {
+    if LOADED.load(Ordering::Acquire) {
+        DatasetStatus::Loaded
+    } else {
+        DatasetStatus::Loading
+    }
+}
+
+/// Version of the dataset - the hex encoded SHA-256 checksum of `data.json`
+pub static DATASET_VERSION: LazyLock<String> =
+    LazyLock::new(|| format!("{:x}", Sha256::digest(DATA)));
+
+/// Time the dataset was last modified, determined at build time
+pub static DATASET_LAST_MODIFIED: LazyLock<SystemTime> = LazyLock::new(|| {
+    let secs = env!("DATASET_LAST_MODIFIED")
+        .parse()
+        .expect("Invalid `DATASET_LAST_MODIFIED` value set at build time");
+    UNIX_EPOCH + Duration::from_secs(secs)
+});
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -97,15 +455,6 @@ mod tests {
 
     use super::*;
 
-    const CONTINENTS: [&str; 6] = [
-        "Africa",
-        "Asia",
-        "Europe",
-        "North America",
-        "Oceania",
-        "South America",
-    ];
-
     macro_rules! assert_valid_text {
         ($val: ident) => {
             assert_eq!(
@@ -140,9 +489,111 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_wonder_index() {
+        assert_eq!(INDEX.json.len(), WONDERS.len());
+        for (i, wonder) in WONDERS.iter().enumerate() {
+            assert_eq!(INDEX.by_slug[&wonder.slug()], i);
+            assert_eq!(
+                serde_json::from_slice::<Wonder>(&INDEX.json[i]).unwrap(),
+                *wonder
+            );
+            for category in &wonder.categories {
+                assert!(INDEX.by_category[category].contains(&i));
+            }
+            assert!(INDEX.by_time_period[&wonder.time_period].contains(&i));
+            assert_eq!(INDEX.continents[i], wonder.continent());
+            assert_eq!(INDEX.coordinates[i], wonder.coordinates());
+            assert_eq!(INDEX.by_build_year[INDEX.build_year_rank[i]], i);
+        }
+
+        INDEX.by_build_year.iter().reduce(|a, b| {
+            assert!(WONDERS[*a].build_year <= WONDERS[*b].build_year);
+            b
+        });
+    }
+
+    #[test]
+    fn test_continent_from_location() {
+        assert_eq!(
+            Continent::from_location("Giza, Egypt, Africa"),
+            Some(Continent::Africa)
+        );
+        assert_eq!(
+            Continent::from_location("Rome, Italy, Europe"),
+            Some(Continent::Europe)
+        );
+        assert_eq!(
+            Continent::from_location("Agra, India, South America"),
+            Some(Continent::SouthAmerica)
+        );
+        assert_eq!(Continent::from_location("Atlantis"), None);
+        assert_eq!(Continent::from_location("Somewhere, Atlantis"), None);
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let giza = Coordinates::from_google_maps(
+            "https://www.google.com/maps/place/The+Great+Pyramid+of+Giza/@29.9791705,31.1342046,17z/data=!3m1!4b1!4m6!3m5!1s0x14584587ac8f291b:0x810c2f3fa2a52424!8m2!3d29.9791705!4d31.1342046!16zL20vMDM2bWs",
+        )
+        .unwrap();
+        assert_eq!(
+            giza,
+            Coordinates {
+                latitude: 29.9791705,
+                longitude: 31.1342046
+            }
+        );
+        assert_eq!(
+            Coordinates::from_google_maps("https://www.google.com/maps/place/Stonehenge"),
+            None
+        );
+
+        let stonehenge = Coordinates {
+            latitude: 51.178882,
+            longitude: -1.826215,
+        };
+        // Roughly 3,600km apart
+        let distance = giza.distance_km(&stonehenge);
+        assert!((3550.0..3650.0).contains(&distance), "{distance}");
+        assert_eq!(giza.distance_km(&giza), 0.0);
+    }
+
+    #[test]
+    fn test_term_vector() {
+        let vectors = TermVector::from_texts(&[
+            "The ancient pyramid, an ancient tomb",
+            "A pyramid and a temple",
+            "A modern bridge",
+        ]);
+        // Common and very short words are ignored, and terms in every text carry no weight
+        assert_eq!(
+            vectors[0]
+                .0
+                .iter()
+                .map(|(t, _)| t.as_str())
+                .collect::<Vec<_>>(),
+            ["ancient", "pyramid", "tomb"]
+        );
+
+        for vector in &vectors {
+            assert!((vector.similarity(vector) - 1.0).abs() < 1e-9);
+        }
+        assert!(vectors[0].similarity(&vectors[1]) > 0.0);
+        assert_eq!(vectors[0].similarity(&vectors[2]), 0.0);
+        assert_eq!(
+            vectors[0].similarity(&vectors[1]),
+            vectors[1].similarity(&vectors[0])
+        );
+        assert_eq!(
+            TermVector::from_texts(&["the and"])[0].similarity(&vectors[0]),
+            0.0
+        );
+    }
+
     #[test]
     fn validate_wonders_data() {
-        assert!(WONDERS.len() > 0);
+        assert!(!WONDERS.is_empty());
 
         // Validate data using `garde`
         WONDERS.iter().for_each(|w| w.validate().unwrap());
@@ -180,10 +631,15 @@ mod tests {
                 assert_valid_text!(summary);
 
                 assert!(
-                    location.contains(',') &&
-                        CONTINENTS.contains(&location.rsplit_once(',').unwrap().1.trim_start()),
+                    Continent::from_location(location).is_some(),
                     "Location must define a continent:\n{location}"
                 );
+                if let Some(google_maps) = google_maps {
+                    assert!(
+                        Coordinates::from_google_maps(google_maps).is_some(),
+                        "Google Maps link must mark a place:\n{google_maps}"
+                    );
+                }
                 assert!(summary.ends_with('.') || summary.ends_with('!'), "Summary must end with proper punctuation:\n{summary}");
 
                 // Unique name
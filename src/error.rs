@@ -8,14 +8,14 @@ use axum::{
 };
 use axum_jsonschema::JsonSchemaRejection;
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::extractors::Json;
+use crate::{extractors::Json, request_id, usage_metrics};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
 /// Represents all errors the API can return
-#[derive(thiserror::Error, Debug, OperationIo, JsonSchema)]
+#[derive(thiserror::Error, Clone, Debug, OperationIo, JsonSchema)]
 #[aide(
     input_with = "axum_jsonschema::Json<Error>",
     output_with = "axum_jsonschema::Json<Error>",
@@ -28,8 +28,16 @@ pub enum Error {
     NoMatchingName(String),
     #[error("The provided lower limit of {0} is greater than the provided upper limit of {1}")]
     ConflictingLimitParams(i16, i16),
+    #[error("Requested {0} wonders, but only {1} match the given filters")]
+    NotEnoughWonders(usize, usize),
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
+    #[error("Too many requests, retry after {0} seconds")]
+    TooManyRequests(u64),
+    #[error("Daily quota exceeded, retry after {0} seconds")]
+    QuotaExceeded(u64),
+    #[error("Invalid API key")]
+    InvalidApiKey,
     // Don't expose additional details about server errors to the client
     #[error("Something went wrong")]
     Internal(String),
@@ -44,23 +52,100 @@ pub enum Error {
 )]
 pub struct ErrorResponse {
     pub message: String,
+    /// Identifies the request in the server logs, as also returned in the `X-Request-Id` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 impl ErrorResponse {
     pub fn new(message: impl Display) -> Self {
         Self {
             message: message.to_string(),
+            request_id: request_id::current(),
+        }
+    }
+}
+
+/// Machine-readable error codes, returned by the `/v1` routes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NoWondersLeft,
+    NoMatchingName,
+    ConflictingLimitParams,
+    NotEnoughWonders,
+    InvalidRequest,
+    TooManyRequests,
+    QuotaExceeded,
+    InvalidApiKey,
+    Internal,
+}
+
+/// For serialising error responses from the `/v1` routes, which include a machine-readable code
+#[derive(Serialize, Deserialize, Debug, OperationIo, JsonSchema)]
+#[aide(
+    input_with = "axum_jsonschema::Json<TypedErrorResponse>",
+    output_with = "axum_jsonschema::Json<TypedErrorResponse>",
+    json_schema
+)]
+pub struct TypedErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Identifies the request in the server logs, as also returned in the `X-Request-Id` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+impl From<&Error> for TypedErrorResponse {
+    fn from(error: &Error) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+            request_id: request_id::current(),
+        }
+    }
+}
+
+impl Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NoWondersLeft => ErrorCode::NoWondersLeft,
+            Self::NoMatchingName(_) => ErrorCode::NoMatchingName,
+            Self::ConflictingLimitParams(_, _) => ErrorCode::ConflictingLimitParams,
+            Self::NotEnoughWonders(_, _) => ErrorCode::NotEnoughWonders,
+            Self::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            Self::TooManyRequests(_) => ErrorCode::TooManyRequests,
+            Self::QuotaExceeded(_) => ErrorCode::QuotaExceeded,
+            Self::InvalidApiKey => ErrorCode::InvalidApiKey,
+            Self::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// Status code used by the `/v1` routes, which distinguish missing wonders from bad requests
+    pub fn status_v1(&self) -> StatusCode {
+        match self {
+            Self::NoWondersLeft | Self::NoMatchingName(_) => StatusCode::NOT_FOUND,
+            Self::ConflictingLimitParams(_, _)
+            | Self::NotEnoughWonders(_, _)
+            | Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Self::TooManyRequests(_) | Self::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
+        usage_metrics::record_error(&self);
         let message = self.to_string();
         let status = match &self {
             Self::NoWondersLeft => StatusCode::BAD_REQUEST,
             Self::NoMatchingName(_) => StatusCode::BAD_REQUEST,
             Self::ConflictingLimitParams(_, _) => StatusCode::BAD_REQUEST,
+            Self::NotEnoughWonders(_, _) => StatusCode::BAD_REQUEST,
             Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::InvalidApiKey => StatusCode::UNAUTHORIZED,
 
             Self::Internal(s) => {
                 tracing::error!("Internal server error: {s}");
@@ -68,7 +153,10 @@ impl IntoResponse for Error {
             }
         };
 
-        (status, Json(ErrorResponse::new(message))).into_response()
+        let mut response = (status, Json(ErrorResponse::new(message))).into_response();
+        // Allows middleware to render the error differently, e.g. for other API versions
+        response.extensions_mut().insert(self);
+        response
     }
 }
 
@@ -84,6 +172,33 @@ impl From<JsonSchemaRejection> for Error {
     }
 }
 
+impl From<garde::Report> for Error {
+    fn from(report: garde::Report) -> Self {
+        Self::InvalidRequest(report.to_string())
+    }
+}
+
+impl From<Error> for tonic::Status {
+    fn from(error: Error) -> Self {
+        usage_metrics::record_error(&error);
+        let message = error.to_string();
+        match error {
+            Error::NoWondersLeft | Error::NoMatchingName(_) => Self::not_found(message),
+            Error::ConflictingLimitParams(_, _)
+            | Error::NotEnoughWonders(_, _)
+            | Error::InvalidRequest(_) => Self::invalid_argument(message),
+            Error::TooManyRequests(_) | Error::QuotaExceeded(_) => {
+                Self::resource_exhausted(message)
+            }
+            Error::InvalidApiKey => Self::unauthenticated(message),
+            Error::Internal(s) => {
+                tracing::error!("Internal server error: {s}");
+                Self::internal(message)
+            }
+        }
+    }
+}
+
 impl From<JsonRejection> for Error {
     fn from(rejection: JsonRejection) -> Self {
         Self::InvalidRequest(rejection.to_string())
@@ -0,0 +1,345 @@
+use std::{io, time::Duration};
+
+use axum_server::tls_rustls::RustlsConfig;
+use garde::Validate;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    data::{self, WONDERS},
+    error::Error,
+    routes::wonders::{
+        filter_wonders, filter_wonders_ignore_empty, find_wonder_by_name, oldest_wonder,
+        random_wonders, sort_wonders, youngest_wonder, RandomWeights, SortBy,
+        WonderParamsFiltering, WonderParamsSorting,
+    },
+    server::ACCEPT_ERROR_BACKOFF,
+};
+
+pub mod proto {
+    tonic::include_proto!("wonders.v0");
+}
+
+/// Number of TLS connections which can be waiting to be served by the gRPC server
+const TLS_ACCEPT_BACKLOG: usize = 32;
+/// Time clients have to complete the TLS handshake before the connection is dropped
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+use proto::{
+    wonders_server::{Wonders, WondersServer},
+    CountResponse, FilterRequest, GetRequest, ListRequest, ListResponse, RandomRequest,
+};
+
+/// Serve the gRPC service on the given listener until the `shutdown` future completes, using TLS
+/// if a TLS configuration is provided
+pub async fn serve(
+    listener: TcpListener,
+    tls: Option<RustlsConfig>,
+    shutdown: impl std::future::Future<Output = ()>,
+) -> Result<(), tonic::transport::Error> {
+    let router = tonic::transport::Server::builder()
+        .trace_fn(|_| tracing::debug_span!("grpc_request"))
+        .add_service(WondersServer::new(WondersService));
+
+    match tls {
+        Some(rustls_config) => {
+            router
+                .serve_with_incoming_shutdown(tls_incoming(listener, rustls_config), shutdown)
+                .await
+        }
+        None => {
+            router
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+                .await
+        }
+    }
+}
+
+/// Accept TLS connections on the given listener. Handshakes use the current certificate from the
+/// TLS configuration, so it's reloaded along with the HTTPS server's
+fn tls_incoming(
+    listener: TcpListener,
+    rustls_config: RustlsConfig,
+) -> ReceiverStream<io::Result<TlsStream<TcpStream>>> {
+    let (sender, receiver) = mpsc::channel(TLS_ACCEPT_BACKLOG);
+
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                result = listener.accept() => match result {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::error!("Failed to accept gRPC connection: {e}");
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                        continue;
+                    }
+                },
+                // The server has shut down
+                () = sender.closed() => break,
+            };
+
+            // Handshakes are done concurrently, so slow clients don't hold up others
+            let acceptor = TlsAcceptor::from(rustls_config.get_inner());
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.send(Ok(stream)).await;
+                    }
+                    Ok(Err(e)) => tracing::debug!("gRPC TLS handshake failed: {e}"),
+                    Err(_) => tracing::debug!("gRPC TLS handshake timed out"),
+                }
+            });
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+// SERVICE ----------------------------------------------------------------------------------------
+/// gRPC service with the same filter semantics as the handlers in [`crate::routes::wonders`]
+#[derive(Debug, Default)]
+pub struct WondersService;
+
+#[tonic::async_trait]
+impl Wonders for WondersService {
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
+        let ListRequest { filter, sort } = request.into_inner();
+        let filter = WonderParamsFiltering::try_from(filter.unwrap_or_default())?;
+        let sort = WonderParamsSorting::try_from(sort.unwrap_or_default())?;
+        filter.validate().map_err(Error::from)?;
+
+        let mut wonders = filter_wonders_ignore_empty(filter)?;
+        sort_wonders(&mut wonders, sort);
+
+        Ok(Response::new(ListResponse {
+            wonders: wonders
+                .into_iter()
+                .map(|i| proto::Wonder::from(&WONDERS[i]))
+                .collect(),
+        }))
+    }
+
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<proto::Wonder>, Status> {
+        let GetRequest { name } = request.into_inner();
+        let wonder = find_wonder_by_name(&name).ok_or(Error::NoMatchingName(name))?;
+
+        Ok(Response::new((&WONDERS[wonder]).into()))
+    }
+
+    async fn random(
+        &self,
+        request: Request<RandomRequest>,
+    ) -> Result<Response<proto::Wonder>, Status> {
+        let RandomRequest { filter, weights } = request.into_inner();
+        let weights = RandomWeights::try_from(weights.unwrap_or_default())?;
+        let wonders = get_filtered(FilterRequest { filter })?;
+        let wonder = random_wonders(&wonders, 1, None, &weights)?[0];
+
+        Ok(Response::new((&WONDERS[wonder]).into()))
+    }
+
+    async fn oldest(
+        &self,
+        request: Request<FilterRequest>,
+    ) -> Result<Response<proto::Wonder>, Status> {
+        let wonders = get_filtered(request.into_inner())?;
+
+        Ok(Response::new(
+            (&WONDERS[oldest_wonder(&wonders).unwrap()]).into(),
+        ))
+    }
+
+    async fn youngest(
+        &self,
+        request: Request<FilterRequest>,
+    ) -> Result<Response<proto::Wonder>, Status> {
+        let wonders = get_filtered(request.into_inner())?;
+
+        Ok(Response::new(
+            (&WONDERS[youngest_wonder(&wonders).unwrap()]).into(),
+        ))
+    }
+
+    async fn count(
+        &self,
+        request: Request<FilterRequest>,
+    ) -> Result<Response<CountResponse>, Status> {
+        let filter =
+            WonderParamsFiltering::try_from(request.into_inner().filter.unwrap_or_default())?;
+        filter.validate().map_err(Error::from)?;
+
+        let wonders = filter_wonders_ignore_empty(filter)?;
+
+        Ok(Response::new(CountResponse {
+            count: wonders.len() as u64,
+        }))
+    }
+}
+
+// UTILS ------------------------------------------------------------------------------------------
+/// Gets all wonders matching the filter in the given request, erroring if no wonders are left
+fn get_filtered(request: FilterRequest) -> Result<Vec<usize>, Error> {
+    let filter = WonderParamsFiltering::try_from(request.filter.unwrap_or_default())?;
+    filter.validate()?;
+
+    filter_wonders(filter)
+}
+
+// CONVERSIONS ------------------------------------------------------------------------------------
+impl From<&data::Wonder> for proto::Wonder {
+    fn from(wonder: &data::Wonder) -> Self {
+        Self {
+            name: wonder.name.clone(),
+            summary: wonder.summary.clone(),
+            location: wonder.location.clone(),
+            build_year: wonder.build_year.into(),
+            time_period: proto::TimePeriod::from(wonder.time_period).into(),
+            links: Some(proto::Links {
+                wiki: wonder.links.wiki.clone(),
+                britannica: wonder.links.britannica.clone(),
+                google_maps: wonder.links.google_maps.clone(),
+                trip_advisor: wonder.links.trip_advisor.clone(),
+                images: wonder.links.images.clone(),
+            }),
+            categories: wonder
+                .categories
+                .iter()
+                .map(|c| proto::Category::from(*c).into())
+                .collect(),
+            popularity: wonder.popularity.into(),
+        }
+    }
+}
+
+impl From<data::TimePeriod> for proto::TimePeriod {
+    fn from(time_period: data::TimePeriod) -> Self {
+        match time_period {
+            data::TimePeriod::Prehistoric => Self::Prehistoric,
+            data::TimePeriod::Ancient => Self::Ancient,
+            data::TimePeriod::Classical => Self::Classical,
+            data::TimePeriod::PostClassical => Self::PostClassical,
+            data::TimePeriod::EarlyModern => Self::EarlyModern,
+            data::TimePeriod::Modern => Self::Modern,
+        }
+    }
+}
+
+impl From<data::Category> for proto::Category {
+    fn from(category: data::Category) -> Self {
+        match category {
+            data::Category::SevenWonders => Self::SevenWonders,
+            data::Category::SevenModernWonders => Self::SevenModernWonders,
+            data::Category::SevenNewWonders => Self::SevenNewWonders,
+            data::Category::Civ5 => Self::Civ5,
+            data::Category::Civ6 => Self::Civ6,
+        }
+    }
+}
+
+/// Parse an optional enum value, where unspecified is treated the same as not provided
+fn parse_enum<P, T>(value: Option<i32>, field: &str) -> Result<Option<T>, Error>
+where
+    P: TryFrom<i32>,
+    Option<T>: From<P>,
+{
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    P::try_from(value)
+        .map(Option::<T>::from)
+        .map_err(|_| Error::InvalidRequest(format!("invalid value {value} for `{field}`")))
+}
+
+/// Parse an optional year, ensuring it fits within the range of years supported by the API
+fn parse_year(value: Option<i32>, field: &str) -> Result<Option<i16>, Error> {
+    value
+        .map(i16::try_from)
+        .transpose()
+        .map_err(|_| Error::InvalidRequest(format!("`{field}` is out of range")))
+}
+
+impl From<proto::TimePeriod> for Option<data::TimePeriod> {
+    fn from(time_period: proto::TimePeriod) -> Self {
+        match time_period {
+            proto::TimePeriod::Unspecified => None,
+            proto::TimePeriod::Prehistoric => Some(data::TimePeriod::Prehistoric),
+            proto::TimePeriod::Ancient => Some(data::TimePeriod::Ancient),
+            proto::TimePeriod::Classical => Some(data::TimePeriod::Classical),
+            proto::TimePeriod::PostClassical => Some(data::TimePeriod::PostClassical),
+            proto::TimePeriod::EarlyModern => Some(data::TimePeriod::EarlyModern),
+            proto::TimePeriod::Modern => Some(data::TimePeriod::Modern),
+        }
+    }
+}
+
+impl From<proto::Category> for Option<data::Category> {
+    fn from(category: proto::Category) -> Self {
+        match category {
+            proto::Category::Unspecified => None,
+            proto::Category::SevenWonders => Some(data::Category::SevenWonders),
+            proto::Category::SevenModernWonders => Some(data::Category::SevenModernWonders),
+            proto::Category::SevenNewWonders => Some(data::Category::SevenNewWonders),
+            proto::Category::Civ5 => Some(data::Category::Civ5),
+            proto::Category::Civ6 => Some(data::Category::Civ6),
+        }
+    }
+}
+
+impl From<proto::SortBy> for Option<SortBy> {
+    fn from(sort_by: proto::SortBy) -> Self {
+        match sort_by {
+            proto::SortBy::Unspecified => None,
+            proto::SortBy::BuildYear => Some(SortBy::BuildYear),
+            proto::SortBy::Alphabetical => Some(SortBy::Alphabetical),
+        }
+    }
+}
+
+impl TryFrom<proto::Filter> for WonderParamsFiltering {
+    type Error = Error;
+
+    fn try_from(filter: proto::Filter) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: filter.name,
+            location: filter.location,
+            time_period: parse_enum::<proto::TimePeriod, _>(filter.time_period, "time_period")?,
+            lower_limit: parse_year(filter.lower_limit, "lower_limit")?,
+            upper_limit: parse_year(filter.upper_limit, "upper_limit")?,
+            category: parse_enum::<proto::Category, _>(filter.category, "category")?,
+        })
+    }
+}
+
+impl TryFrom<proto::Weights> for RandomWeights {
+    type Error = Error;
+
+    fn try_from(weights: proto::Weights) -> Result<Self, Self::Error> {
+        Ok(Self {
+            popularity: weights.weight_by_popularity.unwrap_or(false),
+            category: parse_enum::<proto::Category, _>(weights.favour_category, "favour_category")?,
+            time_period: parse_enum::<proto::TimePeriod, _>(
+                weights.favour_time_period,
+                "favour_time_period",
+            )?,
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<proto::Sort> for WonderParamsSorting {
+    type Error = Error;
+
+    fn try_from(sort: proto::Sort) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sort_by: parse_enum::<proto::SortBy, _>(sort.sort_by, "sort_by")?,
+            sort_reverse: sort.sort_reverse,
+        })
+    }
+}
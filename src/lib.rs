@@ -1,82 +1,198 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
 
 use aide::{
-    axum::{routing::get, ApiRouter},
-    openapi::OpenApi,
+    axum::{
+        routing::{get, get_with},
+        ApiRouter,
+    },
+    openapi::{ApiKeyLocation, OpenApi, SecurityScheme},
     transform::TransformOpenApi,
 };
 use axum::{
     extract::{MatchedPath, Request},
-    http::{self, Method},
+    http, middleware,
+    response::Response,
     Extension, Router,
 };
-use axum_prometheus::PrometheusMetricLayer;
-use routes::{docs, handler_404, wonders};
+use axum_prometheus::{metrics_exporter_prometheus::PrometheusHandle, PrometheusMetricLayer};
+use config::Config;
+use rate_limit::{rate_limit, RateLimits};
+use request_id::{request_id, REQUEST_ID_HEADER};
+use routes::{docs, graphql, handler_404, health, quiz, wonders};
+use security::{cors_layer, SecurityHeaders};
+use telemetry::record_matched_path;
 use tokio::signal;
-use tower_governor::{
-    governor::GovernorConfigBuilder, key_extractor::SmartIpKeyExtractor, GovernorLayer,
-};
 use tower_http::{
-    cors::{Any, CorsLayer},
+    compression::{
+        predicate::{DefaultPredicate, Predicate, SizeAbove},
+        CompressionLayer, CompressionLevel,
+    },
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
+use versioning::{deprecation_headers, typed_errors, ApiVersion, DeprecationHeaders};
 
+pub mod caching;
 pub mod config;
 pub mod data;
 pub mod error;
 pub mod extractors;
+pub mod grpc;
+pub mod rate_limit;
+pub mod request_id;
 pub mod routes;
+pub mod security;
+pub mod server;
+pub mod telemetry;
+pub mod tls;
+pub mod usage_metrics;
+pub mod versioning;
 
 pub const DOCS_ROUTE: &str = "/v0/docs";
 pub const WONDERS_ROUTE: &str = "/v0/wonders";
+pub const GRAPHQL_ROUTE: &str = "/v0/graphql";
+pub const QUIZ_ROUTE: &str = "/v0/quiz";
+pub const V1_DOCS_ROUTE: &str = "/v1/docs";
+pub const V1_WONDERS_ROUTE: &str = "/v1/wonders";
 pub const METRICS_ROUTE: &str = "/metrics";
 pub const HEALTH_ROUTE: &str = "/health";
+pub const HEALTH_LIVE_ROUTE: &str = "/health/live";
+pub const HEALTH_READY_ROUTE: &str = "/health/ready";
+
+/// Returns the token for continuing a shuffle session from the random wonder routes
+pub static SHUFFLE_SESSION_HEADER: http::HeaderName =
+    http::HeaderName::from_static("x-shuffle-session");
+
+/// Handle for rendering metrics. The underlying recorder is global, so it can only be installed
+/// once per process
+static METRIC_HANDLE: LazyLock<PrometheusHandle> =
+    LazyLock::new(|| PrometheusMetricLayer::pair().1);
 
-pub fn get_app() -> Router {
+/// Build the app. API key usage is loaded but never saved, for which
+/// [`get_app_with_rate_limits`] and [`RateLimits::save_usage_periodically`] can be used instead
+pub fn get_app(config: &Config) -> Router {
+    get_app_with_rate_limits(
+        config,
+        RateLimits::new(&config.rate_limit, &config.api_keys),
+    )
+}
+
+/// Build the app using the given rate limits, which can be shared with a task saving API key usage
+pub fn get_app_with_rate_limits(config: &Config, rate_limits: RateLimits) -> Router {
     // API docs generation
     aide::gen::on_error(|error| {
         tracing::error!("Api generation error: {error}");
     });
     aide::gen::extract_schemas(true);
-    let mut api = OpenApi::default();
-
-    // Governor configuration for rate-limiting
-    let governor_conf = Arc::new(
-        GovernorConfigBuilder::default()
-            .burst_size(10)
-            .per_millisecond(200)
-            .key_extractor(SmartIpKeyExtractor)
-            .finish()
-            .expect("Failed setting up `tower_governor` configuration"),
+    let mut api_v0 = OpenApi::default();
+    let mut api_v1 = OpenApi::default();
+
+    // CORS and security headers
+    let cors = cors_layer(&config.cors);
+    let security_headers = middleware::from_fn_with_state(
+        SecurityHeaders::new(&config.security_headers),
+        security::security_headers,
     );
 
-    // CORS
-    let cors = CorsLayer::default()
-        .allow_methods([Method::GET])
-        .allow_origin(Any);
+    // Compression
+    let compression = CompressionLayer::new()
+        .gzip(config.compression.gzip)
+        .br(config.compression.br)
+        .zstd(config.compression.zstd)
+        .quality(
+            config
+                .compression
+                .level
+                .map_or(CompressionLevel::Default, CompressionLevel::Precise),
+        )
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(config.compression.min_size)));
 
     // Metrics
-    let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
+    let prometheus_layer = PrometheusMetricLayer::new();
+    let metric_handle = METRIC_HANDLE.clone();
+
+    // Dataset loading, reported by the health endpoints. After the metrics recorder is installed
+    // so the dataset gauges are recorded
+    LazyLock::force(&health::STARTED_AT);
+    tokio::task::spawn_blocking(|| {
+        data::load();
+        usage_metrics::record_dataset();
+    });
 
-    ApiRouter::new()
-        .nest_api_service(WONDERS_ROUTE, wonders::routes())
-        .nest_api_service(DOCS_ROUTE, docs::routes())
+    // Each version gets its own OpenAPI document
+    let v0 = ApiRouter::new()
+        .nest_api_service(
+            WONDERS_ROUTE,
+            wonders::routes(&config.cache, ApiVersion::V0)
+                .layer(middleware::from_fn(record_matched_path)),
+        )
+        .nest_api_service(
+            DOCS_ROUTE,
+            docs::routes(DOCS_ROUTE).layer(middleware::from_fn(record_matched_path)),
+        )
+        .nest_api_service(
+            QUIZ_ROUTE,
+            quiz::routes().layer(middleware::from_fn(record_matched_path)),
+        )
+        .nest_service(
+            GRAPHQL_ROUTE,
+            graphql::routes().layer(middleware::from_fn(record_matched_path)),
+        )
+        // Only applies to the `/v0` routes above
+        .layer(middleware::from_fn_with_state(
+            DeprecationHeaders::v0(&config.versioning),
+            deprecation_headers,
+        ))
         .api_route(METRICS_ROUTE, get(|| async move { metric_handle.render() }))
         .api_route(
             HEALTH_ROUTE,
             get(|| async { (http::StatusCode::OK, "Healthy!") }),
         )
-        .fallback(handler_404)
-        .finish_api_with(&mut api, api_docs)
+        .api_route(
+            HEALTH_LIVE_ROUTE,
+            get_with(health::get_live, health::get_live_docs),
+        )
+        .api_route(
+            HEALTH_READY_ROUTE,
+            get_with(health::get_ready, health::get_ready_docs),
+        )
+        .finish_api_with(&mut api_v0, |api| api_docs(api, ApiVersion::V0))
+        // Docs generation
+        .layer(Extension(Arc::new(api_v0)));
+
+    let v1 = ApiRouter::new()
+        .nest_api_service(
+            V1_WONDERS_ROUTE,
+            wonders::routes(&config.cache, ApiVersion::V1)
+                .layer(middleware::from_fn(record_matched_path)),
+        )
+        .nest_api_service(
+            V1_DOCS_ROUTE,
+            docs::routes(V1_DOCS_ROUTE).layer(middleware::from_fn(record_matched_path)),
+        )
+        .finish_api_with(&mut api_v1, |api| api_docs(api, ApiVersion::V1))
         // Docs generation
-        .layer(Extension(Arc::new(api)))
+        .layer(Extension(Arc::new(api_v1)));
+
+    Router::new()
+        .merge(v0)
+        .merge(v1)
+        .fallback(handler_404)
+        // Matched paths of the routes above which aren't nested
+        .layer(middleware::from_fn(record_matched_path))
         // Rate-limiting
-        .layer(GovernorLayer {
-            config: governor_conf,
-        })
-        // CORS
+        .layer(middleware::from_fn_with_state(rate_limits, rate_limit))
+        // `/v1` error format, outside of the rate-limiting so its rejections are included, and
+        // inside of the compression so it only sees uncompressed bodies
+        .layer(middleware::from_fn(typed_errors))
+        // Compression
+        .layer(compression)
+        // CORS and security headers
         .layer(cors)
+        .layer(security_headers)
         // Metrics
         .layer(prometheus_layer)
         // Logging
@@ -86,26 +202,62 @@ pub fn get_app() -> Router {
                     let method = req.method();
                     let uri = req.uri();
 
+                    // Recorded later by `record_matched_path` for nested routers
                     let matched_path = req
                         .extensions()
                         .get::<MatchedPath>()
                         .map(axum::extract::MatchedPath::as_str);
+                    // Set by `request_id`
+                    let request_id = req
+                        .headers()
+                        .get(&REQUEST_ID_HEADER)
+                        .and_then(|value| value.to_str().ok());
 
-                    tracing::debug_span!("request", %method, %uri, matched_path)
+                    tracing::info_span!(
+                        "request",
+                        %method,
+                        %uri,
+                        matched_path,
+                        request_id,
+                        status = tracing::field::Empty,
+                        latency_ms = tracing::field::Empty,
+                        otel.kind = "server",
+                    )
+                })
+                .on_response(|res: &Response, latency: Duration, span: &tracing::Span| {
+                    span.record("status", i64::from(res.status().as_u16()));
+                    span.record("latency_ms", latency.as_secs_f64() * 1000.0);
+                    tracing::debug!("finished processing request");
                 })
                 // By default `TraceLayer` will log 5xx responses but we're doing our specific
                 // logging of errors so disable that
                 .on_failure(()),
         )
+        // Request IDs, outside of the logging so they're included in the `request` span
+        .layer(middleware::from_fn(request_id))
         // Timeout
         .layer(TimeoutLayer::with_status_code(
             http::StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(10),
         ))
 }
-fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
+fn api_docs(api: TransformOpenApi, version: ApiVersion) -> TransformOpenApi {
     api.title("World Wonders API")
+        .version(version.as_str())
         .description("Free and open source API providing information about world wonders")
+        .security_scheme(
+            "ApiKey",
+            SecurityScheme::ApiKey {
+                location: ApiKeyLocation::Header,
+                name: "X-Api-Key".to_string(),
+                description: Some(
+                    "Optional key with its own rate limit and daily quota, which can also be \
+                    provided using the `api_key` query parameter"
+                        .to_string(),
+                ),
+                extensions: Default::default(),
+            },
+        )
 }
 
 // For graceful shutdown
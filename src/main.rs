@@ -1,37 +1,57 @@
-use std::net::SocketAddr;
-
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use world_wonders_api::{config::get_config, get_app, shutdown_signal, DOCS_ROUTE};
+use world_wonders_api::{
+    config::get_config, get_app_with_rate_limits, grpc, rate_limit::RateLimits, server,
+    shutdown_signal, telemetry, tls,
+};
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "example_error_handling=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let config = get_config().expect("Failed to read configuration");
 
-    let app = get_app();
+    // Kept until shutdown so that remaining spans are exported
+    let _telemetry = telemetry::init(&config.logging).expect("Failed to set up OTLP exporter");
 
-    let config = get_config().expect("Failed to read configuration");
+    let rate_limits = RateLimits::new(&config.rate_limit, &config.api_keys);
+    let app = get_app_with_rate_limits(&config, rate_limits.clone());
+    let usage_saver = rate_limits
+        .persists_usage()
+        .then(|| tokio::spawn(rate_limits.save_usage_periodically()));
+
+    // Shared by the HTTP and gRPC servers
+    let tls = match &config.network.tls {
+        Some(tls_config) => Some(
+            tls::load_and_watch(tls_config)
+                .await
+                .expect("Failed to load TLS certificate"),
+        ),
+        None => None,
+    };
 
-    let addr = SocketAddr::from((config.network.host, config.network.port));
-    let listener = tokio::net::TcpListener::bind(addr)
+    if config.grpc.enabled {
+        let listener = tokio::net::TcpListener::bind(config.network.host_addr(config.grpc.port))
+            .await
+            .expect("Failed binding gRPC listener");
+        tracing::debug!(
+            "gRPC listening at {}://{}",
+            if tls.is_some() { "https" } else { "http" },
+            listener.local_addr().unwrap()
+        );
+
+        let tls = tls.clone();
+        tokio::spawn(async move {
+            grpc::serve(listener, tls, shutdown_signal())
+                .await
+                .expect("Failed to start gRPC server");
+        });
+    }
+
+    server::serve(app, &config.network, tls)
         .await
-        .expect("Failed binding listener");
-    tracing::debug!("Listening at http://{}", listener.local_addr().unwrap());
-    tracing::debug!(
-        "Example docs available at http://{}{DOCS_ROUTE}",
-        listener.local_addr().unwrap()
-    );
-
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .with_graceful_shutdown(shutdown_signal())
-    .await
-    .expect("Failed to start server");
+        .expect("Failed to start server");
+
+    // API key usage is saved a final time once shutdown starts
+    if let Some(usage_saver) = usage_saver {
+        if let Err(e) = usage_saver.await {
+            tracing::error!("Failed to save API key usage: {e}");
+        }
+    }
 }
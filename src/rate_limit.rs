@@ -0,0 +1,591 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    num::{NonZeroU32, NonZeroU64},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
+    state::keyed::DefaultKeyedStateStore,
+    Quota, RateLimiter,
+};
+use serde::{Deserialize, Serialize};
+use tower_governor::key_extractor::{KeyExtractor, SmartIpKeyExtractor};
+
+use crate::{
+    config::{ApiKeysConfig, RateLimitConfig},
+    error::Error,
+    shutdown_signal,
+};
+
+/// Header used to provide an API key, which can also be provided using the `api_key` query
+/// parameter
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Number of tracked clients after which those whose limits have fully replenished are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+static RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+static QUOTA_LIMIT: HeaderName = HeaderName::from_static("x-quota-limit");
+static QUOTA_REMAINING: HeaderName = HeaderName::from_static("x-quota-remaining");
+static QUOTA_RESET: HeaderName = HeaderName::from_static("x-quota-reset");
+
+/// How often API key usage is saved, if persistence is enabled
+const USAGE_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+type KeyedRateLimiter<K> =
+    RateLimiter<K, DefaultKeyedStateStore<K>, DefaultClock, StateInformationMiddleware>;
+
+/// A rate limit, along with the state of each client against it
+struct Limiter<K: Hash + Eq + Clone = IpAddr> {
+    burst_size: NonZeroU32,
+    period: Duration,
+    limiter: KeyedRateLimiter<K>,
+}
+impl<K: Hash + Eq + Clone> Limiter<K> {
+    fn new(burst_size: NonZeroU32, per_millisecond: NonZeroU64) -> Self {
+        let period = Duration::from_millis(per_millisecond.get());
+        let quota = Quota::with_period(period)
+            .expect("Rate limit period must be non-zero")
+            .allow_burst(burst_size);
+
+        Self {
+            burst_size,
+            period,
+            limiter: RateLimiter::keyed(quota).with_middleware(),
+        }
+    }
+
+    /// Count a request from the given client against the limit
+    fn check(&self, key: &K) -> RateLimitState {
+        if self.limiter.len() > MAX_TRACKED_CLIENTS {
+            self.limiter.retain_recent();
+        }
+
+        match self.limiter.check_key(key) {
+            Ok(snapshot) => {
+                let remaining = snapshot.remaining_burst_capacity();
+                RateLimitState {
+                    limit: self.burst_size.get(),
+                    remaining,
+                    reset: self.period * (self.burst_size.get() - remaining),
+                    retry_after: None,
+                    quota: None,
+                }
+            }
+            Err(not_until) => {
+                let wait = not_until.wait_time_from(self.limiter.clock().now());
+                RateLimitState {
+                    limit: self.burst_size.get(),
+                    remaining: 0,
+                    reset: wait + self.period * (self.burst_size.get() - 1),
+                    retry_after: Some(wait),
+                    quota: None,
+                }
+            }
+        }
+    }
+}
+
+/// A client's state against a rate limit after making a request
+#[derive(Debug, PartialEq)]
+struct RateLimitState {
+    limit: u32,
+    remaining: u32,
+    /// Time until the limit is fully replenished
+    reset: Duration,
+    /// Time until the next request will be allowed, if this request was rejected by the rate limit
+    retry_after: Option<Duration>,
+    /// Usage of the daily quota, for API keys which have one
+    quota: Option<QuotaState>,
+}
+impl RateLimitState {
+    /// Error to respond with if the request was rejected
+    fn rejection(&self) -> Option<Error> {
+        match &self.quota {
+            Some(quota) if quota.exceeded => Some(Error::QuotaExceeded(ceil_secs(quota.reset))),
+            _ => self
+                .retry_after
+                .map(|retry_after| Error::TooManyRequests(ceil_secs(retry_after))),
+        }
+    }
+
+    /// `RateLimit-*` headers as described in
+    /// https://datatracker.ietf.org/doc/draft-ietf-httpapi-ratelimit-headers/, `X-Quota-*` headers
+    /// for API keys with a daily quota, and `Retry-After` for rejected requests
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RATELIMIT_LIMIT.clone(), self.limit.into());
+        headers.insert(RATELIMIT_REMAINING.clone(), self.remaining.into());
+        headers.insert(RATELIMIT_RESET.clone(), ceil_secs(self.reset).into());
+
+        let mut retry_after = self.retry_after;
+        if let Some(quota) = &self.quota {
+            headers.insert(QUOTA_LIMIT.clone(), quota.limit.into());
+            headers.insert(QUOTA_REMAINING.clone(), quota.remaining.into());
+            headers.insert(QUOTA_RESET.clone(), ceil_secs(quota.reset).into());
+            if quota.exceeded {
+                retry_after = Some(quota.reset);
+            }
+        }
+        if let Some(retry_after) = retry_after {
+            headers.insert(header::RETRY_AFTER, ceil_secs(retry_after).into());
+        }
+        headers
+    }
+}
+
+/// An API key's usage of its daily quota after making a request
+#[derive(Debug, PartialEq)]
+struct QuotaState {
+    limit: u64,
+    remaining: u64,
+    /// Time until the quota resets, at midnight UTC
+    reset: Duration,
+    exceeded: bool,
+}
+
+/// Requests made using an API key on a given day
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct DailyUsage {
+    /// Days since the UNIX epoch, in UTC
+    day: u64,
+    requests: u64,
+}
+
+/// An API key, which has its own rate limit and optional daily quota in place of the per-IP limits
+struct ApiKey {
+    limiter: Limiter<()>,
+    daily_quota: Option<u64>,
+    usage: Mutex<DailyUsage>,
+}
+impl ApiKey {
+    /// Count a request against the key's rate limit and daily quota. Requests rejected by the rate
+    /// limit don't count towards the quota
+    fn check(&self, now: SystemTime) -> RateLimitState {
+        let mut state = self.limiter.check(&());
+        if state.retry_after.is_none() {
+            state.quota = self.use_quota(now);
+        }
+        state
+    }
+
+    fn use_quota(&self, now: SystemTime) -> Option<QuotaState> {
+        let limit = self.daily_quota?;
+        let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let day = secs / SECS_PER_DAY;
+
+        let mut usage = self.usage.lock().unwrap();
+        if usage.day != day {
+            *usage = DailyUsage { day, requests: 0 };
+        }
+        let exceeded = usage.requests >= limit;
+        if !exceeded {
+            usage.requests += 1;
+        }
+
+        Some(QuotaState {
+            limit,
+            remaining: limit - usage.requests,
+            reset: Duration::from_secs(SECS_PER_DAY - secs % SECS_PER_DAY),
+            exceeded,
+        })
+    }
+}
+
+/// Rate limits for all routes, used as the state for [`rate_limit`]
+#[derive(Clone)]
+pub struct RateLimits(Arc<RateLimitsInner>);
+struct RateLimitsInner {
+    default: Limiter,
+    /// Route prefixes with their own limits, longest first
+    routes: Vec<(String, Limiter)>,
+    /// API keys by key, along with their names
+    api_keys: HashMap<String, (String, ApiKey)>,
+    usage_file: Option<PathBuf>,
+}
+impl RateLimits {
+    pub fn new(config: &RateLimitConfig, api_keys_config: &ApiKeysConfig) -> Self {
+        let mut routes: Vec<_> = config
+            .routes
+            .iter()
+            .map(|route| {
+                (
+                    route.prefix.clone(),
+                    Limiter::new(route.burst_size, route.per_millisecond),
+                )
+            })
+            .collect();
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+        let usage_file = api_keys_config.usage_file.as_ref().map(PathBuf::from);
+        let mut usage = usage_file
+            .as_ref()
+            .filter(|path| path.exists())
+            .map(|path| {
+                std::fs::read(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| {
+                        serde_json::from_slice::<HashMap<String, DailyUsage>>(&bytes)
+                            .map_err(|e| e.to_string())
+                    })
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to load API key usage from {path:?}: {e}");
+                        HashMap::new()
+                    })
+            })
+            .unwrap_or_default();
+
+        let api_keys = api_keys_config
+            .keys
+            .iter()
+            .map(|key| {
+                let api_key = ApiKey {
+                    limiter: Limiter::new(key.burst_size, key.per_millisecond),
+                    daily_quota: key.daily_quota,
+                    usage: Mutex::new(usage.remove(&key.name).unwrap_or_default()),
+                };
+                (key.key.clone(), (key.name.clone(), api_key))
+            })
+            .collect();
+
+        Self(Arc::new(RateLimitsInner {
+            default: Limiter::new(config.burst_size, config.per_millisecond),
+            routes,
+            api_keys,
+            usage_file,
+        }))
+    }
+
+    /// Whether API key usage should be persisted
+    pub fn persists_usage(&self) -> bool {
+        self.0.usage_file.is_some()
+    }
+
+    /// Save the daily usage of each API key to the configured usage file, if any
+    pub fn save_usage(&self) -> std::io::Result<()> {
+        let Some(path) = &self.0.usage_file else {
+            return Ok(());
+        };
+
+        let usage: HashMap<_, _> = self
+            .0
+            .api_keys
+            .values()
+            .map(|(name, api_key)| (name, *api_key.usage.lock().unwrap()))
+            .collect();
+
+        // Write to a temporary file first so the usage file is never left partially written
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&usage)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Periodically save the daily usage of each API key, to be run as a background task. Usage is
+    /// also saved once the server starts shutting down, after which the task finishes, so it
+    /// should be awaited before exiting
+    pub async fn save_usage_periodically(self) {
+        let mut interval = tokio::time::interval(USAGE_SAVE_INTERVAL);
+        let mut shutdown = std::pin::pin!(shutdown_signal());
+        loop {
+            let shutting_down = tokio::select! {
+                _ = interval.tick() => false,
+                () = &mut shutdown => true,
+            };
+            if let Err(e) = self.save_usage() {
+                tracing::error!("Failed to save API key usage: {e}");
+            }
+            if shutting_down {
+                return;
+            }
+        }
+    }
+
+    /// Limit for the given path, using the override with the longest matching prefix if any.
+    /// Prefixes only match whole path segments
+    fn for_path(&self, path: &str) -> &Limiter {
+        self.0
+            .routes
+            .iter()
+            .find(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str()).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/')
+                })
+            })
+            .map_or(&self.0.default, |(_, limiter)| limiter)
+    }
+}
+
+// MIDDLEWARE -------------------------------------------------------------------------------------
+/// Middleware limiting the rate of requests from each API key, or from each client IP address for
+/// requests without one, adding `RateLimit-*` headers to all responses
+pub async fn rate_limit(State(limits): State<RateLimits>, req: Request, next: Next) -> Response {
+    let state = match get_api_key(&req) {
+        Some(key) => {
+            let Some((name, api_key)) = limits.0.api_keys.get(&key) else {
+                return Error::InvalidApiKey.into_response();
+            };
+            tracing::debug!(api_key = name, "Request using API key");
+            api_key.check(SystemTime::now())
+        }
+        None => {
+            let Ok(ip) = SmartIpKeyExtractor.extract(&req) else {
+                return Error::Internal("Failed to determine the client IP address".to_string())
+                    .into_response();
+            };
+            limits.for_path(req.uri().path()).check(&ip)
+        }
+    };
+
+    let mut response = match state.rejection() {
+        Some(error) => error.into_response(),
+        None => next.run(req).await,
+    };
+    response.headers_mut().extend(state.headers());
+    response
+}
+
+// UTILS ------------------------------------------------------------------------------------------
+#[derive(Deserialize)]
+struct ApiKeyQuery {
+    api_key: Option<String>,
+}
+
+/// Get the API key provided with a request, from either the header or the query parameter
+fn get_api_key(req: &Request) -> Option<String> {
+    if let Some(value) = req.headers().get(API_KEY_HEADER) {
+        return Some(String::from_utf8_lossy(value.as_bytes()).into_owned());
+    }
+
+    axum::extract::Query::<ApiKeyQuery>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|query| query.0.api_key)
+}
+
+/// Whole number of seconds in the given duration, rounded up
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::config::{ApiKeyConfig, RouteRateLimitConfig};
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn limiter(burst_size: u32, per_millisecond: u64) -> Limiter {
+        Limiter::new(
+            NonZeroU32::new(burst_size).unwrap(),
+            NonZeroU64::new(per_millisecond).unwrap(),
+        )
+    }
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_limiter_check() {
+        let limiter = limiter(2, 60_000);
+
+        let state = limiter.check(&CLIENT);
+        assert_eq!(state.remaining, 1);
+        assert_eq!(state.reset, Duration::from_secs(60));
+        assert_eq!(state.retry_after, None);
+        assert_eq!(limiter.check(&CLIENT).remaining, 0);
+
+        let state = limiter.check(&CLIENT);
+        assert_eq!(state.remaining, 0);
+        let retry_after = state.retry_after.unwrap();
+        assert!(retry_after > Duration::from_secs(59) && retry_after <= Duration::from_secs(60));
+        assert_eq!(state.reset, retry_after + Duration::from_secs(60));
+
+        // Clients are limited separately
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(limiter.check(&other).retry_after, None);
+    }
+
+    #[test]
+    fn test_rate_limit_state_headers() {
+        let headers = RateLimitState {
+            limit: 10,
+            remaining: 0,
+            reset: Duration::from_millis(2_100),
+            retry_after: Some(Duration::from_millis(100)),
+            quota: None,
+        }
+        .headers();
+        assert_eq!(headers["ratelimit-limit"], "10");
+        assert_eq!(headers["ratelimit-remaining"], "0");
+        assert_eq!(headers["ratelimit-reset"], "3");
+        assert_eq!(headers["retry-after"], "1");
+
+        let headers = RateLimitState {
+            limit: 10,
+            remaining: 10,
+            reset: Duration::ZERO,
+            retry_after: None,
+            quota: None,
+        }
+        .headers();
+        assert_eq!(headers["ratelimit-reset"], "0");
+        assert!(!headers.contains_key("retry-after"));
+        assert!(!headers.contains_key("x-quota-limit"));
+
+        let state = RateLimitState {
+            limit: 10,
+            remaining: 9,
+            reset: Duration::from_secs(1),
+            retry_after: None,
+            quota: Some(QuotaState {
+                limit: 1000,
+                remaining: 0,
+                reset: Duration::from_secs(3600),
+                exceeded: true,
+            }),
+        };
+        let headers = state.headers();
+        assert_eq!(headers["x-quota-limit"], "1000");
+        assert_eq!(headers["x-quota-remaining"], "0");
+        assert_eq!(headers["x-quota-reset"], "3600");
+        assert_eq!(headers["retry-after"], "3600");
+        assert!(matches!(
+            state.rejection(),
+            Some(Error::QuotaExceeded(3600))
+        ));
+    }
+
+    #[test]
+    fn test_api_key_quota() {
+        let api_key = ApiKey {
+            limiter: Limiter::new(NonZeroU32::new(10).unwrap(), NonZeroU64::new(1).unwrap()),
+            daily_quota: Some(2),
+            usage: Mutex::default(),
+        };
+        // 23:00 UTC
+        let now = UNIX_EPOCH + Duration::from_secs(SECS_PER_DAY * 100 + 23 * 60 * 60);
+
+        let quota = api_key.check(now).quota.unwrap();
+        assert_eq!((quota.remaining, quota.exceeded), (1, false));
+        assert_eq!(quota.reset, Duration::from_secs(60 * 60));
+        assert_eq!(api_key.check(now).quota.unwrap().remaining, 0);
+
+        let state = api_key.check(now);
+        assert!(state.quota.as_ref().unwrap().exceeded);
+        assert!(matches!(
+            state.rejection(),
+            Some(Error::QuotaExceeded(3600))
+        ));
+
+        // Resets the next day
+        let quota = api_key
+            .check(now + Duration::from_secs(60 * 60))
+            .quota
+            .unwrap();
+        assert_eq!((quota.remaining, quota.exceeded), (1, false));
+        assert_eq!(
+            *api_key.usage.lock().unwrap(),
+            DailyUsage {
+                day: 101,
+                requests: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_api_key() {
+        let request = |uri: &str, header: Option<&str>| {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(header) = header {
+                builder = builder.header(API_KEY_HEADER, header);
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+
+        assert_eq!(get_api_key(&request("/v0/wonders", None)), None);
+        assert_eq!(
+            get_api_key(&request("/v0/wonders?name=a&api_key=abc", None)),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            get_api_key(&request("/v0/wonders?api_key=abc", Some("def"))),
+            Some("def".to_string())
+        );
+    }
+
+    #[test]
+    fn test_save_usage() {
+        let usage_file = std::env::temp_dir().join(format!(
+            "world-wonders-api-usage-{}.json",
+            std::process::id()
+        ));
+        let api_keys_config = ApiKeysConfig {
+            keys: vec![ApiKeyConfig {
+                name: "test".to_string(),
+                key: "abc".to_string(),
+                burst_size: NonZeroU32::new(10).unwrap(),
+                per_millisecond: NonZeroU64::new(1).unwrap(),
+                daily_quota: Some(10),
+            }],
+            file: None,
+            usage_file: Some(usage_file.to_string_lossy().into_owned()),
+        };
+        let rate_limit_config = RateLimitConfig {
+            burst_size: NonZeroU32::new(1).unwrap(),
+            per_millisecond: NonZeroU64::new(1).unwrap(),
+            routes: vec![],
+        };
+
+        let limits = RateLimits::new(&rate_limit_config, &api_keys_config);
+        let (_, api_key) = &limits.0.api_keys["abc"];
+        api_key.check(SystemTime::now());
+        api_key.check(SystemTime::now());
+        limits.save_usage().unwrap();
+
+        // Usage is restored on startup
+        let limits = RateLimits::new(&rate_limit_config, &api_keys_config);
+        let (_, api_key) = &limits.0.api_keys["abc"];
+        assert_eq!(api_key.usage.lock().unwrap().requests, 2);
+
+        std::fs::remove_file(usage_file).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limits_for_path() {
+        let route = |prefix: &str, burst_size| RouteRateLimitConfig {
+            prefix: prefix.to_string(),
+            burst_size: NonZeroU32::new(burst_size).unwrap(),
+            per_millisecond: NonZeroU64::new(1_000).unwrap(),
+        };
+        let limits = RateLimits::new(
+            &RateLimitConfig {
+                burst_size: NonZeroU32::new(1).unwrap(),
+                per_millisecond: NonZeroU64::new(1_000).unwrap(),
+                routes: vec![route("/v0", 2), route("/v0/wonders/random", 3)],
+            },
+            &ApiKeysConfig::default(),
+        );
+
+        assert_eq!(limits.for_path("/health").burst_size.get(), 1);
+        assert_eq!(limits.for_path("/v0/wonders").burst_size.get(), 2);
+        assert_eq!(limits.for_path("/v0/wonders/random").burst_size.get(), 3);
+        assert_eq!(limits.for_path("/v0/wonders/random/").burst_size.get(), 3);
+        // Only whole segments match
+        assert_eq!(limits.for_path("/v0/wonders/randomize").burst_size.get(), 2);
+        assert_eq!(limits.for_path("/v01").burst_size.get(), 1);
+    }
+}
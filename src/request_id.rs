@@ -0,0 +1,65 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request ID accepted from clients, so they can't bloat the logs
+const MAX_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// ID of the request currently being handled, so it can be included in error responses
+    static REQUEST_ID: String;
+}
+
+/// ID of the request currently being handled, if called while handling a request
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Middleware using the client's `X-Request-Id` header, or generating a new ID if there isn't a
+/// valid one, and returning it in the response
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = match req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(id) if is_valid(id) => id.to_string(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    };
+    let value = HeaderValue::from_str(&id).unwrap();
+    // Makes the ID available to the `request` span
+    req.headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), value.clone());
+
+    let mut response = REQUEST_ID.scope(id, next.run(req)).await;
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), value);
+    response
+}
+
+// UTILS ------------------------------------------------------------------------------------------
+/// Whether a client-provided ID is short and only contains printable ASCII characters
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_LENGTH && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("abc-123"));
+        assert!(is_valid(&uuid::Uuid::new_v4().to_string()));
+        assert!(!is_valid(""));
+        assert!(!is_valid("has spaces"));
+        assert!(!is_valid(&"a".repeat(MAX_LENGTH + 1)));
+    }
+}
@@ -10,11 +10,12 @@ use aide::{
 };
 use axum::Extension;
 
-use crate::{extractors::Json, DOCS_ROUTE};
+use crate::extractors::Json;
 
 const API_FILE_ROUTE: &str = "/api.json";
 
-pub fn routes() -> ApiRouter {
+/// Documentation routes, to be nested under the given `docs_route`
+pub fn routes(docs_route: &str) -> ApiRouter {
     let router: ApiRouter = ApiRouter::new()
         // Serve the documentation JSON file
         .route(API_FILE_ROUTE, get(serve_docs))
@@ -22,7 +23,7 @@ pub fn routes() -> ApiRouter {
         .api_route_with(
             "/",
             get_with(
-                Scalar::new(format!("{DOCS_ROUTE}{API_FILE_ROUTE}"))
+                Scalar::new(format!("{docs_route}{API_FILE_ROUTE}"))
                     .with_title("World Wonder API Docs")
                     .axum_handler(),
                 |op| op.description("This documentation page."),
@@ -0,0 +1,242 @@
+use async_graphql::{http::GraphiQLSource, EmptyMutation, EmptySubscription, Object, Schema};
+use async_graphql_axum::GraphQL;
+use axum::{
+    response::{Html, IntoResponse},
+    routing::get,
+    Router,
+};
+use garde::Validate;
+use strum::IntoEnumIterator;
+
+use crate::{
+    data::{Category, TimePeriod, Wonder, WONDERS},
+    error::{Error, Result},
+    routes::wonders::{
+        filter_wonders, filter_wonders_ignore_empty, find_wonder_by_name, get_categories,
+        oldest_wonder, random_wonders, sort_wonders, youngest_wonder, RandomWeights, SortBy,
+        WonderParamsFiltering, WonderParamsSorting,
+    },
+    GRAPHQL_ROUTE,
+};
+
+pub type WondersSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// ROUTES -----------------------------------------------------------------------------------------
+pub fn routes() -> Router {
+    Router::new().route(
+        "/",
+        get(graphiql).post_service(GraphQL::new(build_schema())),
+    )
+}
+
+/// Build the GraphQL schema for querying wonders
+pub fn build_schema() -> WondersSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription).finish()
+}
+
+/// Interactive playground for exploring the GraphQL schema and running queries
+async fn graphiql() -> impl IntoResponse {
+    Html(
+        GraphiQLSource::build()
+            .endpoint(GRAPHQL_ROUTE)
+            .title("World Wonders API GraphQL Playground")
+            .finish(),
+    )
+}
+
+// UTILS ------------------------------------------------------------------------------------------
+/// Gets all wonders matching the given filters, erroring if no wonders are left
+fn get_filtered(filter: Option<WonderParamsFiltering>) -> Result<Vec<usize>> {
+    let filter = filter.unwrap_or_default();
+    filter.validate()?;
+
+    filter_wonders(filter)
+}
+
+// QUERIES ----------------------------------------------------------------------------------------
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// All wonders, after applying the given filters and sort methods
+    async fn wonders(
+        &self,
+        filter: Option<WonderParamsFiltering>,
+        sort: Option<WonderParamsSorting>,
+    ) -> async_graphql::Result<Vec<&'static Wonder>> {
+        let filter = filter.unwrap_or_default();
+        let sort = sort.unwrap_or_default();
+        filter.validate()?;
+        sort.validate()?;
+
+        let mut wonders = filter_wonders_ignore_empty(filter)?;
+        sort_wonders(&mut wonders, sort);
+
+        Ok(wonders.into_iter().map(|i| &WONDERS[i]).collect())
+    }
+
+    /// Number of wonders, after applying the given filters
+    async fn count(&self, filter: Option<WonderParamsFiltering>) -> async_graphql::Result<usize> {
+        let filter = filter.unwrap_or_default();
+        filter.validate()?;
+
+        Ok(filter_wonders_ignore_empty(filter)?.len())
+    }
+
+    /// Specific wonder matching the given name. Note that the name will be parsed as lowercase
+    /// letters with spaces replaced with '-'.
+    async fn wonder(&self, name: String) -> async_graphql::Result<&'static Wonder> {
+        let wonder = find_wonder_by_name(&name).ok_or(Error::NoMatchingName(name))?;
+        Ok(&WONDERS[wonder])
+    }
+
+    /// Random wonder, after applying the given filters, with wonders made more likely to be
+    /// selected using the given weights
+    async fn random(
+        &self,
+        filter: Option<WonderParamsFiltering>,
+        weights: Option<RandomWeights>,
+    ) -> async_graphql::Result<&'static Wonder> {
+        let wonders = get_filtered(filter)?;
+        let wonder = random_wonders(&wonders, 1, None, &weights.unwrap_or_default())?[0];
+        Ok(&WONDERS[wonder])
+    }
+
+    /// Oldest (least recently built) wonder, after applying the given filters
+    async fn oldest(
+        &self,
+        filter: Option<WonderParamsFiltering>,
+    ) -> async_graphql::Result<&'static Wonder> {
+        Ok(&WONDERS[oldest_wonder(&get_filtered(filter)?).unwrap()])
+    }
+
+    /// Youngest (most recently built) wonder, after applying the given filters
+    async fn youngest(
+        &self,
+        filter: Option<WonderParamsFiltering>,
+    ) -> async_graphql::Result<&'static Wonder> {
+        Ok(&WONDERS[youngest_wonder(&get_filtered(filter)?).unwrap()])
+    }
+
+    /// All available wonder categories
+    async fn categories(&self, exclude_games: Option<bool>) -> Vec<Category> {
+        get_categories(exclude_games)
+    }
+
+    /// All available human history time periods for wonders' construction times
+    async fn time_periods(&self) -> Vec<TimePeriod> {
+        TimePeriod::iter().collect()
+    }
+
+    /// All valid options for sorting wonders
+    async fn sort_by(&self) -> Vec<SortBy> {
+        SortBy::iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    async fn execute(query: &str) -> async_graphql::Response {
+        build_schema().execute(query).await
+    }
+
+    #[tokio::test]
+    async fn test_query_wonders() {
+        let res = execute("{ wonders { name } }").await;
+        assert!(res.errors.is_empty());
+        let data = res.data.into_json().unwrap();
+        assert_eq!(data["wonders"].as_array().unwrap().len(), WONDERS.len());
+
+        let res = execute(
+            "{ wonders(filter: { category: SEVEN_WONDERS }, sort: { sortBy: BUILD_YEAR }) { name buildYear } }",
+        )
+        .await;
+        assert!(res.errors.is_empty());
+        let data = res.data.into_json().unwrap();
+        let wonders = data["wonders"].as_array().unwrap();
+        assert_eq!(wonders.len(), 7);
+        wonders.iter().reduce(|a, b| {
+            assert!(a["buildYear"].as_i64() <= b["buildYear"].as_i64());
+            b
+        });
+        // Only the requested fields are returned
+        assert_eq!(
+            wonders[0].as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["name", "buildYear"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_count() {
+        let res = execute("{ count(filter: { category: SEVEN_WONDERS }) }").await;
+        assert_eq!(res.data.into_json().unwrap(), json!({ "count": 7 }));
+
+        let res = execute("{ count(filter: { name: \"abcdefghijk\" }) }").await;
+        assert_eq!(res.data.into_json().unwrap(), json!({ "count": 0 }));
+    }
+
+    #[tokio::test]
+    async fn test_query_wonder() {
+        let res = execute("{ wonder(name: \"alhambra\") { name links { wiki } } }").await;
+        let data = res.data.into_json().unwrap();
+        assert_eq!(data["wonder"]["name"], "Alhambra");
+
+        let res = execute("{ wonder(name: \"a\") { name } }").await;
+        assert_eq!(
+            res.errors[0].message,
+            Error::NoMatchingName("a".to_string()).to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_random() {
+        let res = execute(
+            "{ random(filter: { category: SEVEN_WONDERS }, weights: { weightByPopularity: true, \
+            favourTimePeriod: ANCIENT }) { name } }",
+        )
+        .await;
+        let data = res.data.into_json().unwrap();
+        assert!(WONDERS
+            .iter()
+            .filter(|w| w.categories.contains(&Category::SevenWonders))
+            .any(|w| data["random"]["name"] == w.name));
+    }
+
+    #[tokio::test]
+    async fn test_query_oldest_youngest() {
+        let res = execute("{ oldest { name } youngest { name } }").await;
+        let data = res.data.into_json().unwrap();
+
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+        assert_eq!(
+            data["oldest"]["name"],
+            WONDERS[oldest_wonder(&wonders).unwrap()].name
+        );
+        assert_eq!(
+            data["youngest"]["name"],
+            WONDERS[youngest_wonder(&wonders).unwrap()].name
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_errors() {
+        let res = execute("{ random(filter: { name: \"abcdefghijk\" }) { name } }").await;
+        assert_eq!(res.errors[0].message, Error::NoWondersLeft.to_string());
+
+        let res = execute("{ count(filter: { lowerLimit: 500, upperLimit: 400 }) }").await;
+        assert_eq!(
+            res.errors[0].message,
+            Error::ConflictingLimitParams(500, 400).to_string()
+        );
+
+        // Validation
+        let res = execute("{ count(filter: { name: \"\" }) }").await;
+        assert!(!res.errors.is_empty());
+        assert_eq!(res.data.into_json().unwrap(), Value::Null);
+    }
+}
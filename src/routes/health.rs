@@ -0,0 +1,107 @@
+use std::{sync::LazyLock, time::Instant};
+
+use aide::{axum::IntoApiResponse, transform::TransformOperation};
+use axum::{http::StatusCode, response::IntoResponse};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    data::{dataset_status, DatasetStatus, DATASET_VERSION, WONDERS},
+    extractors::Json,
+};
+
+/// Time the API was started, for reporting its uptime
+pub(crate) static STARTED_AT: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Status of the API, returned by the health endpoints
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct Health {
+    /// Whether the API is ready to serve requests
+    pub ready: bool,
+    pub dataset: DatasetHealth,
+    pub build: BuildInfo,
+    pub uptime_seconds: u64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct DatasetHealth {
+    pub status: DatasetStatus,
+    /// Only provided once the dataset is loaded
+    pub wonder_count: Option<usize>,
+    /// Hex encoded SHA-256 checksum of the dataset
+    pub checksum: &'static str,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Commit the API was built from, if known at build time
+    pub git_commit: Option<&'static str>,
+}
+
+impl Health {
+    pub fn new(status: DatasetStatus) -> Self {
+        let loaded = status == DatasetStatus::Loaded;
+        Self {
+            ready: loaded,
+            dataset: DatasetHealth {
+                status,
+                // Accessing the wonders while loading would wait for them to be loaded
+                wonder_count: loaded.then(|| WONDERS.len()),
+                checksum: &DATASET_VERSION,
+            },
+            build: BuildInfo {
+                version: env!("CARGO_PKG_VERSION"),
+                git_commit: option_env!("GIT_COMMIT"),
+            },
+            uptime_seconds: STARTED_AT.elapsed().as_secs(),
+        }
+    }
+}
+
+// HANDLERS ----------------------------------------------------------------------------------------
+// LIVENESS
+pub async fn get_live() -> impl IntoApiResponse {
+    Json(Health::new(dataset_status()))
+}
+pub fn get_live_docs(op: TransformOperation) -> TransformOperation {
+    op.summary("Liveness")
+        .description("Status of the API, which is always `200` while the API is running")
+}
+
+// READINESS
+pub async fn get_ready() -> impl IntoApiResponse {
+    let health = Health::new(dataset_status());
+    let status = if health.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(health)).into_response()
+}
+pub fn get_ready_docs(op: TransformOperation) -> TransformOperation {
+    op.summary("Readiness")
+        .description("Status of the API, which is `503` while the dataset is loading")
+        // Not inferred from the handler, which can respond with either status
+        .response_with::<200, Json<Health>, _>(|res| res.description("Ready"))
+        .response_with::<503, Json<Health>, _>(|res| res.description("Not ready"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_health() {
+        let health = Health::new(DatasetStatus::Loading);
+        assert!(!health.ready);
+        assert_eq!(health.dataset.wonder_count, None);
+        assert_eq!(health.dataset.checksum.len(), 64);
+
+        let health = Health::new(DatasetStatus::Loaded);
+        assert!(health.ready);
+        assert_eq!(health.dataset.wonder_count, Some(WONDERS.len()));
+    }
+}
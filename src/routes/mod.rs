@@ -1,6 +1,9 @@
 use axum::{http::StatusCode, response::IntoResponse};
 
 pub mod docs;
+pub mod graphql;
+pub mod health;
+pub mod quiz;
 pub mod wonders;
 
 pub async fn handler_404() -> impl IntoResponse {
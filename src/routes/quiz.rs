@@ -0,0 +1,671 @@
+use aide::{
+    axum::{routing::get_with, ApiRouter, IntoApiResponse},
+    transform::TransformOperation,
+};
+use axum::{http::header, response::IntoResponse};
+use axum_valid::Garde;
+use garde::Validate;
+use rand::{prelude::*, rngs::Xoshiro256PlusPlus};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{
+    data::{Category, Continent, INDEX, WONDERS},
+    error::Error,
+    extractors::{Json, Query},
+    routes::wonders::{filter_wonders, period, Period, Resolution, WonderParamsFiltering},
+    versioning::{error_docs, ApiVersion},
+};
+
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+#[garde(allow_unvalidated)]
+pub struct QuizParams {
+    /// Number of questions, which defaults to 10
+    #[garde(range(min = 1, max = MAX_QUESTIONS))]
+    pub count: Option<usize>,
+    /// Defaults to `medium`
+    pub difficulty: Option<Difficulty>,
+    /// Only generate questions of this type
+    pub question_type: Option<QuestionType>,
+    /// The same seed generates the same quiz for the same parameters and dataset
+    pub seed: Option<u64>,
+}
+
+const DEFAULT_QUESTIONS: usize = 10;
+const MAX_QUESTIONS: usize = 50;
+/// Attempts made at generating each question, as some wonders can't be used for some questions
+const ATTEMPTS_PER_QUESTION: usize = 10;
+/// Replaces the name of the wonder in summaries
+const REDACTED: &str = "___";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    /// Fewer choices, which are easier to tell apart
+    Easy,
+    Medium,
+    /// Choices are similar to the answer, e.g. wonders from the same time period
+    Hard,
+}
+impl Difficulty {
+    fn choices(self) -> usize {
+        match self {
+            Self::Easy => 3,
+            Self::Medium | Self::Hard => 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionType {
+    /// Which of the wonders was built first
+    WhichIsOlder,
+    /// Which continent a wonder is on
+    WhichContinent,
+    /// Which category a wonder belongs to
+    WhichCategory,
+    /// Which range of years a wonder was built in
+    WhenBuilt,
+    /// Which wonder a summary describes
+    IdentifyFromSummary,
+}
+
+/// Multiple-choice questions generated from the dataset
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Quiz {
+    /// Provide this as the `seed` query parameter to get the same quiz again
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub questions: Vec<Question>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Question {
+    #[serde(rename = "type")]
+    pub question_type: QuestionType,
+    pub question: String,
+    /// Summary of the wonder with its name removed, for `identify_from_summary` questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub choices: Vec<String>,
+    /// Index of the correct choice
+    pub answer: usize,
+}
+impl Question {
+    /// Create a question with the correct choice shuffled in among the others
+    fn new(
+        question_type: QuestionType,
+        question: String,
+        correct: String,
+        others: Vec<String>,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let answer = rng.random_range(0..=others.len());
+        let mut choices = others;
+        choices.insert(answer, correct);
+
+        Self {
+            question_type,
+            question,
+            summary: None,
+            choices,
+            answer,
+        }
+    }
+}
+
+// ROUTES -----------------------------------------------------------------------------------------
+pub fn routes() -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/", get_with(get_quiz, get_quiz_docs))
+        .api_route(
+            "/question-types",
+            get_with(get_question_types, get_question_types_docs),
+        )
+}
+
+// UTILS ------------------------------------------------------------------------------------------
+/// Generate up to `count` questions about the given wonders, using the given seed. Fewer questions
+/// are generated if there aren't enough wonders for the requested question type and difficulty
+pub fn generate_quiz(
+    wonders: &[usize],
+    count: usize,
+    difficulty: Difficulty,
+    question_type: Option<QuestionType>,
+    seed: u64,
+) -> Vec<Question> {
+    // Unlike `StdRng`, the algorithm is guaranteed to stay the same, so seeds remain reproducible
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+    let question_types = match question_type {
+        Some(question_type) => vec![question_type],
+        None => QuestionType::iter().collect(),
+    };
+
+    // Each wonder is asked about once before any are repeated
+    let mut subjects = wonders.to_vec();
+    subjects.shuffle(&mut rng);
+
+    let mut questions = Vec::with_capacity(count);
+    for &subject in subjects.iter().cycle().take(count * ATTEMPTS_PER_QUESTION) {
+        if questions.len() == count {
+            break;
+        }
+        let question_type = *question_types.choose(&mut rng).unwrap();
+        if let Some(question) =
+            generate_question(question_type, subject, wonders, difficulty, &mut rng)
+        {
+            questions.push(question);
+        }
+    }
+    questions
+}
+
+/// Generate a question of the given type about the subject, using the other given wonders as
+/// choices where needed
+fn generate_question(
+    question_type: QuestionType,
+    subject: usize,
+    wonders: &[usize],
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+) -> Option<Question> {
+    let wonder = &WONDERS[subject];
+    let choices = difficulty.choices();
+
+    match question_type {
+        QuestionType::WhichIsOlder => {
+            // Minimum number of years between the choices
+            let gap = match difficulty {
+                Difficulty::Easy => 1000,
+                Difficulty::Medium => 200,
+                Difficulty::Hard => 1,
+            };
+            let mut candidates: Vec<usize> = wonders
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    difficulty != Difficulty::Hard || WONDERS[i].time_period == wonder.time_period
+                })
+                .collect();
+            candidates.shuffle(rng);
+
+            let mut picked = vec![subject];
+            for i in candidates {
+                if picked.len() == choices {
+                    break;
+                }
+                if picked
+                    .iter()
+                    .all(|&p| WONDERS[p].build_year.abs_diff(WONDERS[i].build_year) >= gap)
+                {
+                    picked.push(i);
+                }
+            }
+            if picked.len() < choices {
+                return None;
+            }
+
+            picked.sort_unstable_by_key(|&i| WONDERS[i].build_year);
+            let oldest = picked.remove(0);
+            picked.shuffle(rng);
+            Some(Question::new(
+                question_type,
+                "Which of these wonders was built first?".to_string(),
+                WONDERS[oldest].name.clone(),
+                picked.iter().map(|&i| WONDERS[i].name.clone()).collect(),
+                rng,
+            ))
+        }
+        QuestionType::WhichContinent => {
+            let continent = INDEX.continents[subject];
+            let others = Continent::iter()
+                .filter(|&c| c != continent)
+                .sample(rng, choices - 1);
+            Some(Question::new(
+                question_type,
+                format!("Which continent is {} located in?", wonder.name),
+                continent_name(continent).to_string(),
+                others
+                    .into_iter()
+                    .map(|c| continent_name(c).to_string())
+                    .collect(),
+                rng,
+            ))
+        }
+        QuestionType::WhichCategory => {
+            let category = *wonder.categories.choose(rng)?;
+            let others = Category::iter()
+                .filter(|c| !wonder.categories.contains(c))
+                .sample(rng, choices - 1);
+            if others.is_empty() {
+                return None;
+            }
+            Some(Question::new(
+                question_type,
+                format!("Which of these lists includes {}?", wonder.name),
+                category_name(category).to_string(),
+                others
+                    .into_iter()
+                    .map(|c| category_name(c).to_string())
+                    .collect(),
+                rng,
+            ))
+        }
+        QuestionType::WhenBuilt => {
+            let resolution = match difficulty {
+                Difficulty::Easy => Resolution::Millennium,
+                Difficulty::Medium => Resolution::Century,
+                Difficulty::Hard => Resolution::Decade,
+            };
+            let latest = WONDERS.iter().map(|w| w.build_year).max()?;
+            let built = period(wonder.build_year, resolution);
+
+            // Neighbouring periods, randomly split between those before and after so the answer
+            // isn't always in the middle. Periods after the latest wonder would be giveaways
+            let mut before = Vec::new();
+            let mut after = Vec::new();
+            let mut after_wanted = rng.random_range(0..choices);
+            while before.len() + after.len() < choices - 1 {
+                let last = after.last().unwrap_or(&built);
+                match next_period(last, resolution) {
+                    Some(next) if after.len() < after_wanted && next.start <= i32::from(latest) => {
+                        after.push(next);
+                    }
+                    _ => {
+                        after_wanted = after.len();
+                        before.push(previous_period(
+                            before.last().unwrap_or(&built),
+                            resolution,
+                        )?);
+                    }
+                }
+            }
+
+            // Choices are in chronological order
+            let answer = before.len();
+            let choices = before
+                .into_iter()
+                .rev()
+                .chain(std::iter::once(built))
+                .chain(after)
+                .map(|period| period.label)
+                .collect();
+            Some(Question {
+                question_type,
+                question: format!("When was {} built?", wonder.name),
+                summary: None,
+                choices,
+                answer,
+            })
+        }
+        QuestionType::IdentifyFromSummary => {
+            // Easier questions prefer dissimilar wonders as the other choices, and harder
+            // questions prefer similar wonders
+            let similar = |i: usize| {
+                WONDERS[i].time_period == wonder.time_period
+                    || WONDERS[i]
+                        .categories
+                        .iter()
+                        .any(|c| wonder.categories.contains(c))
+            };
+            let mut candidates: Vec<usize> =
+                wonders.iter().copied().filter(|&i| i != subject).collect();
+            candidates.shuffle(rng);
+            match difficulty {
+                Difficulty::Easy => candidates.sort_by_key(|&i| similar(i)),
+                Difficulty::Medium => {}
+                Difficulty::Hard => candidates.sort_by_key(|&i| !similar(i)),
+            }
+            if candidates.len() < choices - 1 {
+                return None;
+            }
+
+            let mut question = Question::new(
+                question_type,
+                "Which wonder does this describe?".to_string(),
+                wonder.name.clone(),
+                candidates[..choices - 1]
+                    .iter()
+                    .map(|&i| WONDERS[i].name.clone())
+                    .collect(),
+                rng,
+            );
+            question.summary = Some(redact_name(&wonder.summary, &wonder.name));
+            Some(question)
+        }
+    }
+}
+
+/// The period after the given one
+fn next_period(current: &Period, resolution: Resolution) -> Option<Period> {
+    // There is no year 0
+    let year = if current.end == -1 {
+        1
+    } else {
+        current.end + 1
+    };
+    Some(period(i16::try_from(year).ok()?, resolution))
+}
+
+/// The period before the given one
+fn previous_period(current: &Period, resolution: Resolution) -> Option<Period> {
+    // Year 0 is treated as 1 BCE
+    Some(period(i16::try_from(current.start - 1).ok()?, resolution))
+}
+
+/// Remove the given name from the text, along with any distinctive words from the name, e.g.
+/// "Giza" from "Great Pyramid of Giza"
+pub fn redact_name(text: &str, name: &str) -> String {
+    const IGNORED_WORDS: [&str; 4] = ["The", "And", "Of", "At"];
+
+    // ASCII case-insensitive, so byte offsets are the same in the lowercase text
+    let lowercase_text = text.to_ascii_lowercase();
+    let lowercase_name = name.to_ascii_lowercase();
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = 0;
+    for (start, _) in lowercase_text.match_indices(&lowercase_name) {
+        redacted.push_str(&text[rest..start]);
+        redacted.push_str(REDACTED);
+        rest = start + name.len();
+    }
+    redacted.push_str(&text[rest..]);
+
+    // Only capitalised words, so e.g. "pyramid" is kept in "the largest Egyptian pyramid"
+    let name_words: Vec<&str> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3 && !IGNORED_WORDS.contains(word))
+        .collect();
+    let mut result = String::with_capacity(redacted.len());
+    let mut word = String::new();
+    for c in redacted.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+        if name_words.contains(&word.as_str()) {
+            result.push_str(REDACTED);
+        } else {
+            result.push_str(&word);
+        }
+        word.clear();
+        result.push(c);
+    }
+    result.pop();
+    result
+}
+
+/// Readable name of a continent, for use as a choice
+fn continent_name(continent: Continent) -> &'static str {
+    match continent {
+        Continent::Africa => "Africa",
+        Continent::Asia => "Asia",
+        Continent::Europe => "Europe",
+        Continent::NorthAmerica => "North America",
+        Continent::Oceania => "Oceania",
+        Continent::SouthAmerica => "South America",
+    }
+}
+
+/// Readable name of a category, for use as a choice
+fn category_name(category: Category) -> &'static str {
+    match category {
+        Category::SevenWonders => "Seven Wonders of the Ancient World",
+        Category::SevenModernWonders => "Seven Wonders of the Modern World",
+        Category::SevenNewWonders => "New Seven Wonders of the World",
+        Category::Civ5 => "Civilization V",
+        Category::Civ6 => "Civilization VI",
+    }
+}
+
+// HANDLERS ----------------------------------------------------------------------------------------
+// GET QUIZ
+async fn get_quiz(
+    Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(quiz_params)): Garde<Query<QuizParams>>,
+) -> impl IntoApiResponse {
+    let wonders = match filter_wonders(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
+    };
+    // Kept within the range of integers which can be represented exactly in JavaScript
+    let seed = quiz_params
+        .seed
+        .unwrap_or_else(|| rand::rng().random_range(0..1 << 53));
+    let difficulty = quiz_params.difficulty.unwrap_or(Difficulty::Medium);
+
+    let questions = generate_quiz(
+        &wonders,
+        quiz_params.count.unwrap_or(DEFAULT_QUESTIONS),
+        difficulty,
+        quiz_params.question_type,
+        seed,
+    );
+    if questions.is_empty() {
+        return Error::InvalidRequest(
+            "Not enough wonders match the given filters to generate questions".to_string(),
+        )
+        .into_response();
+    }
+
+    let quiz = Json(Quiz {
+        seed,
+        difficulty,
+        questions,
+    });
+    if quiz_params.seed.is_some() {
+        quiz.into_response()
+    } else {
+        // Quizzes without a seed are random, so shouldn't be cached
+        ([(header::CACHE_CONTROL, "no-store")], quiz).into_response()
+    }
+}
+fn get_quiz_docs(op: TransformOperation) -> TransformOperation {
+    op.summary("Quiz")
+        .description(
+            "Generate multiple-choice questions about the wonders remaining after applying filters \
+            defined by query parameters. The same `seed` always generates the same quiz for the \
+            same parameters, and the seed used is returned so a random quiz can be repeated. \
+            Fewer questions than requested may be returned if there aren't enough wonders for \
+            the requested question type and difficulty",
+        )
+        .response_with::<200, Json<Quiz>, _>(|res| {
+            let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+            res.example(Quiz {
+                seed: 42,
+                difficulty: Difficulty::Medium,
+                questions: generate_quiz(&wonders, 2, Difficulty::Medium, None, 42),
+            })
+        })
+        .with(|op| error_docs(op, ApiVersion::V0, Error::NoWondersLeft))
+}
+
+// GET QUESTION TYPES
+async fn get_question_types() -> impl IntoApiResponse {
+    Json(QuestionType::iter().collect::<Vec<QuestionType>>()).into_response()
+}
+fn get_question_types_docs(op: TransformOperation) -> TransformOperation {
+    op.summary("Quiz question types")
+        .description("Get all types of questions which can be generated for quizzes")
+        .response_with::<200, Json<Vec<QuestionType>>, _>(|res| {
+            res.example(QuestionType::iter().collect::<Vec<QuestionType>>())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::get, Router};
+    use axum_test::TestServer;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{extract_response, get_route_server};
+
+    fn all_wonders() -> Vec<usize> {
+        (0..WONDERS.len()).collect()
+    }
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_redact_name() {
+        assert_eq!(
+            redact_name(
+                "The largest Egyptian pyramid. The Great Pyramid of Giza is in Giza.",
+                "Great Pyramid of Giza"
+            ),
+            "The largest Egyptian pyramid. The ___ is in ___."
+        );
+        assert_eq!(
+            redact_name(
+                "A seated Zeus. The statue was lost.",
+                "Statue of Zeus at Olympia"
+            ),
+            "A seated ___. The statue was lost."
+        );
+
+        for wonder in WONDERS.iter() {
+            let summary = redact_name(&wonder.summary, &wonder.name).to_lowercase();
+            assert!(!summary.contains(&wonder.name.to_lowercase()));
+        }
+    }
+
+    #[test]
+    fn test_neighbouring_periods() {
+        let first_ce = period(1, Resolution::Century);
+        let first_bce = previous_period(&first_ce, Resolution::Century).unwrap();
+        assert_eq!(first_bce.label, "1st century BCE");
+        assert_eq!(
+            next_period(&first_bce, Resolution::Century).unwrap(),
+            first_ce
+        );
+        assert!(
+            previous_period(&period(i16::MIN, Resolution::Decade), Resolution::Decade).is_none()
+        );
+    }
+
+    #[test]
+    fn test_generate_quiz() {
+        let wonders = all_wonders();
+
+        // Reproducible using the same seed
+        let quiz = generate_quiz(&wonders, 20, Difficulty::Medium, None, 1);
+        assert_eq!(quiz.len(), 20);
+        assert_eq!(
+            quiz,
+            generate_quiz(&wonders, 20, Difficulty::Medium, None, 1)
+        );
+        assert_ne!(
+            quiz,
+            generate_quiz(&wonders, 20, Difficulty::Medium, None, 2)
+        );
+
+        for difficulty in Difficulty::iter() {
+            for question in generate_quiz(&wonders, 50, difficulty, None, 3) {
+                assert!(question.answer < question.choices.len());
+                assert!(question.choices.len() <= difficulty.choices());
+                let mut choices = question.choices.clone();
+                choices.sort_unstable();
+                choices.dedup();
+                assert_eq!(choices.len(), question.choices.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_quiz_answers() {
+        let wonders = all_wonders();
+        let by_name = |name: &str| {
+            WONDERS
+                .iter()
+                .find(|w| w.name == name)
+                .expect("Unknown wonder")
+        };
+
+        for question_type in QuestionType::iter() {
+            let questions = generate_quiz(&wonders, 10, Difficulty::Hard, Some(question_type), 4);
+            assert_eq!(questions.len(), 10);
+
+            for question in questions {
+                assert_eq!(question.question_type, question_type);
+                let answer = &question.choices[question.answer];
+                match question_type {
+                    QuestionType::WhichIsOlder => {
+                        let oldest = question
+                            .choices
+                            .iter()
+                            .map(|name| by_name(name))
+                            .min_by_key(|w| w.build_year)
+                            .unwrap();
+                        assert_eq!(&oldest.name, answer);
+                    }
+                    QuestionType::IdentifyFromSummary => {
+                        let summary = question.summary.unwrap();
+                        assert!(!summary.contains(answer.as_str()));
+                        assert!(by_name(answer).summary.len() >= summary.len());
+                    }
+                    QuestionType::WhenBuilt => {
+                        assert_eq!(question.choices.len(), Difficulty::Hard.choices());
+                        assert!(answer.ends_with("s BCE") || answer.ends_with("s CE"));
+                    }
+                    QuestionType::WhichContinent | QuestionType::WhichCategory => {
+                        assert!(question.summary.is_none());
+                    }
+                }
+            }
+        }
+
+        // Not enough wonders for the choices
+        let questions = generate_quiz(
+            &[0, 1],
+            5,
+            Difficulty::Medium,
+            Some(QuestionType::WhichIsOlder),
+            5,
+        );
+        assert!(questions.is_empty());
+    }
+
+    // UNIT TESTS - ROUTES
+    #[tokio::test]
+    async fn test_get_quiz() {
+        let server = get_route_server!(get_quiz);
+
+        let quiz = extract_response!(
+            server,
+            Quiz,
+            "/?count=5&difficulty=easy&category=SevenWonders"
+        );
+        assert_eq!(quiz.questions.len(), 5);
+        assert_eq!(quiz.difficulty, Difficulty::Easy);
+
+        // The returned seed gives the same quiz
+        let response = server
+            .get(&format!(
+                "/?count=5&difficulty=easy&category=SevenWonders&seed={}",
+                quiz.seed
+            ))
+            .await;
+        assert!(response.maybe_header(header::CACHE_CONTROL).is_none());
+        assert_eq!(response.json::<Quiz>().questions, quiz.questions);
+
+        server.get("/?count=0").await.assert_status_bad_request();
+        server
+            .get("/?name=abcdefghijk")
+            .await
+            .assert_status_bad_request();
+        server
+            .get("/?name=colosseum&question_type=which_is_older")
+            .await
+            .assert_status_bad_request();
+    }
+
+    #[tokio::test]
+    async fn test_get_question_types() {
+        let server = get_route_server!(get_question_types);
+
+        let question_types = extract_response!(server, Vec<QuestionType>);
+        assert_eq!(question_types, QuestionType::iter().collect::<Vec<_>>());
+    }
+}
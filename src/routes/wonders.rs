@@ -1,40 +1,110 @@
+use std::collections::BTreeMap;
+
 use aide::{
     axum::{routing::get_with, ApiRouter, IntoApiResponse},
     transform::TransformOperation,
 };
-use axum::{extract::Path, response::IntoResponse};
+use async_graphql::{Enum, InputObject};
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::{header, HeaderValue, Uri},
+    middleware,
+    response::{IntoResponse, Response},
+};
 use axum_valid::Garde;
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use chrono_tz::Tz;
 use garde::Validate;
-use rand::prelude::*;
+use rand::{prelude::*, rngs::Xoshiro256PlusPlus};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::{
-    data::{Category, TimePeriod, Wonder, WONDERS},
-    error::{Error, ErrorResponse, Result},
+    caching::{conditional_get, CacheHeaders},
+    config::CacheConfig,
+    data::{Category, Continent, TimePeriod, Wonder, INDEX, WONDERS},
+    error::{Error, Result},
     extractors::{Json, Query},
+    usage_metrics,
+    versioning::{error_docs, errors_docs, ApiVersion},
+    SHUFFLE_SESSION_HEADER,
 };
 
-#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate, InputObject)]
 #[garde(allow_unvalidated)]
 pub struct WonderParamsFiltering {
     #[garde(length(min = 1, max = 150))]
-    name: Option<String>,
+    pub name: Option<String>,
     #[garde(length(min = 1, max = 150))]
-    location: Option<String>,
-    time_period: Option<TimePeriod>,
-    lower_limit: Option<i16>,
-    upper_limit: Option<i16>,
-    category: Option<Category>,
+    pub location: Option<String>,
+    pub time_period: Option<TimePeriod>,
+    pub lower_limit: Option<i16>,
+    pub upper_limit: Option<i16>,
+    pub category: Option<Category>,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Validate)]
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate, InputObject)]
 #[garde(allow_unvalidated)]
 pub struct WonderParamsSorting {
-    sort_by: Option<SortBy>,
-    sort_reverse: Option<bool>,
+    pub sort_by: Option<SortBy>,
+    pub sort_reverse: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Validate)]
+pub struct PaginationParams {
+    /// Page number, starting from 1
+    #[garde(range(min = 1))]
+    pub page: Option<usize>,
+    /// Number of items per page
+    #[garde(range(min = 1, max = MAX_PER_PAGE))]
+    pub per_page: Option<usize>,
+}
+
+const DEFAULT_PER_PAGE: usize = 20;
+const MAX_PER_PAGE: usize = 100;
+/// Error message for paginated lists of wonders, which can't be grouped
+const GROUP_BY_UNSUPPORTED: &str = "`group_by` is not supported for paginated lists of wonders";
+
+/// Page of results, as returned by paginated `/v1` routes
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Total number of items across all pages
+    pub total: usize,
+    pub total_pages: usize,
+}
+impl<T: Clone> Page<T> {
+    /// Get the page of the given items requested by the pagination parameters
+    pub fn new(items: &[T], params: &PaginationParams) -> Self {
+        let page = params.page.unwrap_or(1);
+        let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        let start = (page - 1).saturating_mul(per_page).min(items.len());
+        let end = start.saturating_add(per_page).min(items.len());
+
+        Self {
+            items: items[start..end].to_vec(),
+            page,
+            per_page,
+            total: items.len(),
+            total_pages: items.len().div_ceil(per_page),
+        }
+    }
+
+    /// Convert each of the items in the page
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            per_page: self.per_page,
+            total: self.total,
+            total_pages: self.total_pages,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -42,23 +112,472 @@ pub struct CategoriesParams {
     exclude_games: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    Enum,
+)]
 pub enum SortBy {
     BuildYear,
     Alphabetical,
 }
 
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+#[garde(allow_unvalidated)]
+pub struct GroupingParams {
+    /// Return a map from each group to its wonders, instead of a single list
+    pub group_by: Option<GroupBy>,
+}
+
+/// Response from routes which group their results if `group_by` is provided
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum MaybeGrouped<T> {
+    /// Without `group_by`
+    Ungrouped(T),
+    /// With `group_by`, mapping each group to its results
+    Grouped(BTreeMap<String, T>),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    TimePeriod,
+    /// Wonders in multiple categories appear in each of their groups
+    Category,
+    Continent,
+    /// Grouped by the century the wonder was built in, e.g. "3rd century BCE"
+    Century,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+#[garde(allow_unvalidated)]
+pub struct TimelineParams {
+    /// Size of each period on the timeline, which defaults to `century`
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// e.g. "1920s CE", covering 1920 to 1929 CE
+    Decade,
+    /// e.g. "20th century CE", covering 1901 to 2000 CE
+    Century,
+    /// e.g. "3rd millennium BCE", covering 3000 to 2001 BCE
+    Millennium,
+}
+
+/// Span of years on a timeline
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Period {
+    /// First year, negative for years BCE
+    pub start: i32,
+    /// Last year (inclusive), negative for years BCE
+    pub end: i32,
+    /// Readable name, e.g. "3rd century BCE"
+    pub label: String,
+}
+
+/// Period on a timeline, with the wonders built during it
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TimelineBucket<T> {
+    /// Readable name of the period, e.g. "3rd century BCE"
+    pub label: String,
+    /// First year of the period, negative for years BCE
+    pub start: i32,
+    /// Last year (inclusive) of the period, negative for years BCE
+    pub end: i32,
+    /// Sorted by build year
+    pub wonders: Vec<T>,
+}
+impl<T> TimelineBucket<T> {
+    /// Convert each of the wonders in the bucket
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> TimelineBucket<U> {
+        TimelineBucket {
+            label: self.label,
+            start: self.start,
+            end: self.end,
+            wonders: self.wonders.into_iter().map(f).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Validate)]
+pub struct CompareParams {
+    /// Name of the first wonder, as lowercase letters with spaces replaced with '-'
+    #[garde(length(min = 1, max = 150))]
+    pub a: String,
+    /// Name of the second wonder, as lowercase letters with spaces replaced with '-'
+    #[garde(length(min = 1, max = 150))]
+    pub b: String,
+}
+
+/// Wonders built at most this many years apart are considered to have been built close together.
+/// This stands in for whether the wonders were ever contemporaries, which can't be determined as
+/// the dataset doesn't record when wonders were destroyed
+pub const BUILT_CLOSE_TOGETHER_YEARS: u16 = 100;
+
+/// Two wonders, along with facts derived from comparing them
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WonderComparison<T> {
+    pub a: T,
+    pub b: T,
+    /// Number of years between the wonders being built
+    pub age_difference_years: u16,
+    /// Whether the wonders were built at most `BUILT_CLOSE_TOGETHER_YEARS` years apart. Only based
+    /// on their build years, so it doesn't tell whether both wonders were ever standing at once
+    pub built_close_together: bool,
+    /// Great-circle distance between the wonders, if both of their locations are known
+    pub distance_km: Option<f64>,
+    /// Categories both wonders belong to
+    pub shared_categories: Vec<Category>,
+    pub same_time_period: bool,
+}
+impl<T> WonderComparison<T> {
+    /// Convert both of the wonders
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> WonderComparison<U> {
+        WonderComparison {
+            a: f(self.a),
+            b: f(self.b),
+            age_difference_years: self.age_difference_years,
+            built_close_together: self.built_close_together,
+            distance_km: self.distance_km,
+            shared_categories: self.shared_categories,
+            same_time_period: self.same_time_period,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+pub struct RelatedParams {
+    /// Number of related wonders, which defaults to 5
+    #[garde(range(min = 1, max = MAX_RELATED))]
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_RELATED: usize = 5;
+const MAX_RELATED: usize = 20;
+/// Distance at which the geographic score of related wonders drops to around a third
+const RELATED_DISTANCE_KM: f64 = 2000.0;
+
+/// Wonder related to another, along with how closely it's related
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedWonder<T> {
+    pub wonder: T,
+    /// Average of the scores in the breakdown, from 0 to 1
+    pub score: f64,
+    pub breakdown: RelatedScores,
+}
+impl<T> RelatedWonder<T> {
+    /// Convert the wonder
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> RelatedWonder<U> {
+        RelatedWonder {
+            wonder: f(self.wonder),
+            score: self.score,
+            breakdown: self.breakdown,
+        }
+    }
+}
+
+/// How closely two wonders are related in different ways, each from 0 to 1
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct RelatedScores {
+    /// Proportion of their combined categories which the wonders share
+    pub categories: f64,
+    /// How close their time periods are
+    pub time_period: f64,
+    /// How close they are geographically, or 0 if either location is unknown
+    pub distance: f64,
+    /// How similar their summaries are, by TF-IDF cosine similarity
+    pub summary: f64,
+}
+impl RelatedScores {
+    fn average(&self) -> f64 {
+        (self.categories + self.time_period + self.distance + self.summary) / 4.0
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Validate)]
+pub struct StatsParams {
+    /// Width, in years, of each bucket in the build year histogram
+    #[garde(range(min = 1, max = MAX_BUCKET_WIDTH))]
+    pub bucket_width: Option<u16>,
+}
+
+const DEFAULT_BUCKET_WIDTH: u16 = 500;
+const MAX_BUCKET_WIDTH: u16 = 10_000;
+
+/// Aggregate statistics about wonders
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WonderStats {
+    pub count: usize,
+    /// Number of wonders in each category, including empty categories
+    pub by_category: BTreeMap<Category, usize>,
+    /// Number of wonders from each time period, including empty time periods
+    pub by_time_period: BTreeMap<TimePeriod, usize>,
+    /// Number of wonders on each continent, including empty continents
+    pub by_continent: BTreeMap<Continent, usize>,
+    /// Not provided if there are no wonders
+    pub build_year: Option<BuildYearStats>,
+    /// Number of wonders built in each bucket of years, from the oldest to the youngest wonder
+    pub build_year_histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BuildYearStats {
+    pub min: i16,
+    pub max: i16,
+    pub median: f64,
+    pub mean: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct HistogramBucket {
+    /// First year in the bucket
+    pub start: i32,
+    /// First year after the bucket
+    pub end: i32,
+    pub count: usize,
+}
+
+/// Seed used for the wonder of the day, which is the same for every instance of the API
+const DAILY_SEED: u64 = 0;
+
+/// Value of the `session` parameter which starts a new shuffle session
+const NEW_SESSION: &str = "new";
+
+/// Position in a [`shuffled_sequence`] of wonders, so random wonders aren't repeated until all of
+/// the filtered wonders have been returned. Sessions are stateless, being passed back and forth as
+/// tokens, so they continue across restarts and instances of the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShuffleSession {
+    pub seed: u64,
+    /// Number of positions in the sequence already served
+    pub served: i64,
+}
+impl ShuffleSession {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, served: 0 }
+    }
+
+    /// Take the next `count` distinct wonders in the sequence of the given wonders. Unless unseen
+    /// wonders are favoured, weights only change the order wonders are taken in, as each is taken
+    /// once before any are repeated
+    pub fn take(
+        &mut self,
+        wonders: &[usize],
+        count: usize,
+        weights: &RandomWeights,
+    ) -> Result<Vec<usize>> {
+        if count > wonders.len() {
+            return Err(Error::NotEnoughWonders(count, wonders.len()));
+        }
+
+        let len = wonders.len() as i64;
+        let mut seen = Vec::new();
+        if weights.unseen {
+            // Replay the current cycle to find the wonders already served in it
+            for index in self.served - self.served.rem_euclid(len)..self.served {
+                seen.push(unseen_favouring_draw(
+                    wonders, self.seed, index, weights, &seen,
+                ));
+            }
+        }
+
+        let mut taken = Vec::with_capacity(count);
+        while taken.len() < count {
+            let wonder = if weights.unseen {
+                if self.served.rem_euclid(len) == 0 {
+                    seen.clear();
+                }
+                let wonder = unseen_favouring_draw(wonders, self.seed, self.served, weights, &seen);
+                seen.push(wonder);
+                wonder
+            } else {
+                shuffled_sequence(wonders, self.seed, self.served, weights)
+            };
+            self.served = self
+                .served
+                .checked_add(1)
+                .ok_or_else(|| Error::InvalidRequest("Shuffle session has ended".to_string()))?;
+            // Wonders from the end of one cycle may appear at the start of the next
+            if !taken.contains(&wonder) {
+                taken.push(wonder);
+            }
+        }
+        Ok(taken)
+    }
+}
+impl std::fmt::Display for ShuffleSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:x}-{:x}", self.seed, self.served)
+    }
+}
+impl std::str::FromStr for ShuffleSession {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.split_once('-')
+            .and_then(|(seed, served)| {
+                Some(Self {
+                    seed: u64::from_str_radix(seed, 16).ok()?,
+                    served: i64::from_str_radix(served, 16).ok().filter(|&n| n >= 0)?,
+                })
+            })
+            .ok_or_else(|| Error::InvalidRequest(format!("Invalid shuffle session '{s}'")))
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+pub struct RandomParams {
+    /// Return this many distinct wonders as a list, instead of a single wonder
+    #[garde(range(min = 1))]
+    pub count: Option<usize>,
+    /// The same seed selects the same wonders for the same filters and dataset
+    #[garde(skip)]
+    pub seed: Option<u64>,
+    /// `new` to start a shuffle session, or the token from the `X-Shuffle-Session` header of the
+    /// previous response to continue one. Wonders aren't repeated within a session until all of
+    /// the filtered wonders have been returned, as long as the filters stay the same
+    #[garde(length(min = 1, max = 40))]
+    pub session: Option<String>,
+    /// Make wonders more likely to be selected in proportion to their popularity
+    #[garde(skip)]
+    pub weight_by_popularity: Option<bool>,
+    /// Make wonders in this category 5 times as likely to be selected
+    #[garde(skip)]
+    pub favour_category: Option<Category>,
+    /// Make wonders from this time period 5 times as likely to be selected
+    #[garde(skip)]
+    pub favour_time_period: Option<TimePeriod>,
+    /// In a shuffle session, make wonders which haven't been returned in the current cycle 5
+    /// times as likely to be selected, rather than never repeating them until the cycle ends
+    #[garde(skip)]
+    pub favour_unseen: Option<bool>,
+}
+
+/// Favoured wonders are this many times as likely to be selected at random
+const FAVOURED_WEIGHT: f64 = 5.0;
+
+/// How likely each wonder is to be selected at random, relative to the others
+#[derive(Debug, Clone, Copy, Default, InputObject)]
+pub struct RandomWeights {
+    /// Make wonders more likely to be selected in proportion to their popularity
+    #[graphql(name = "weightByPopularity", default)]
+    pub popularity: bool,
+    /// Make wonders in this category 5 times as likely to be selected
+    #[graphql(name = "favourCategory")]
+    pub category: Option<Category>,
+    /// Make wonders from this time period 5 times as likely to be selected
+    #[graphql(name = "favourTimePeriod")]
+    pub time_period: Option<TimePeriod>,
+    /// Make wonders not yet served in the current cycle of a [`ShuffleSession`] 5 times as likely
+    /// to be selected, instead of never repeating them
+    #[graphql(skip)]
+    pub unseen: bool,
+}
+impl RandomWeights {
+    pub fn new(params: &RandomParams) -> Self {
+        Self {
+            popularity: params.weight_by_popularity.unwrap_or(false),
+            category: params.favour_category,
+            time_period: params.favour_time_period,
+            unseen: params.favour_unseen.unwrap_or(false),
+        }
+    }
+
+    /// Whether every wonder is equally likely to be selected, apart from any favouring of unseen
+    /// wonders
+    fn is_uniform(&self) -> bool {
+        !self.popularity && self.category.is_none() && self.time_period.is_none()
+    }
+
+    /// Weight of the given wonder, apart from any favouring of unseen wonders
+    pub fn weight(&self, wonder: usize) -> f64 {
+        let wonder = &WONDERS[wonder];
+        let mut weight = if self.popularity {
+            f64::from(wonder.popularity)
+        } else {
+            1.0
+        };
+        if self
+            .category
+            .is_some_and(|category| wonder.categories.contains(&category))
+        {
+            weight *= FAVOURED_WEIGHT;
+        }
+        if self.time_period == Some(wonder.time_period) {
+            weight *= FAVOURED_WEIGHT;
+        }
+        weight
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Default, Validate)]
+pub struct DailyParams {
+    /// Date in the format `YYYY-MM-DD`, which defaults to today
+    #[garde(length(max = 10))]
+    pub date: Option<String>,
+    /// IANA time zone, e.g. `Europe/Oslo`, or UTC offset, e.g. `-08:00`, used to determine today's
+    /// date when no date is provided. Defaults to `UTC`. A `+` in an offset must be
+    /// percent-encoded as `%2B`, e.g. `%2B05:30`
+    #[garde(length(max = 64))]
+    pub tz: Option<String>,
+}
+
 // ROUTES -----------------------------------------------------------------------------------------
-pub fn routes() -> ApiRouter {
-    ApiRouter::new()
-        .api_route("/", get_with(get_all_wonders, get_all_wonders_docs))
+pub fn routes(cache_config: &CacheConfig, version: ApiVersion) -> ApiRouter {
+    let router = match version {
+        ApiVersion::V0 => ApiRouter::new().api_route(
+            "/",
+            get_with(get_all_wonders, move |op| get_all_wonders_docs(op, version)),
+        ),
+        ApiVersion::V1 => ApiRouter::new().api_route(
+            "/",
+            get_with(get_all_wonders_paginated, get_all_wonders_paginated_docs),
+        ),
+    };
+
+    router
         .api_route(
             "/count",
-            get_with(get_count_wonders, get_count_wonders_docs),
+            get_with(get_count_wonders, move |op| {
+                get_count_wonders_docs(op, version)
+            }),
+        )
+        .api_route(
+            "/stats",
+            get_with(get_wonder_stats, move |op| {
+                get_wonder_stats_docs(op, version)
+            }),
+        )
+        .api_route(
+            "/timeline",
+            get_with(get_wonder_timeline, move |op| {
+                get_wonder_timeline_docs(op, version)
+            }),
+        )
+        .api_route(
+            "/compare",
+            get_with(get_wonder_comparison, move |op| {
+                get_wonder_comparison_docs(op, version)
+            }),
         )
         .api_route(
             "/categories",
-            get_with(get_wonder_categories, get_wonder_categories_docs),
+            get_with(get_wonder_categories, move |op| {
+                get_wonder_categories_docs(op, version)
+            }),
         )
         .api_route(
             "/time-periods",
@@ -68,38 +587,77 @@ pub fn routes() -> ApiRouter {
             "/sort-by",
             get_with(get_wonder_sort_by, get_wonder_sort_by_docs),
         )
-        .api_route(
-            "/random",
-            get_with(get_random_wonder, get_random_wonder_docs),
-        )
         .api_route(
             "/oldest",
-            get_with(get_oldest_wonder, get_oldest_wonder_docs),
+            get_with(get_oldest_wonder, move |op| {
+                get_oldest_wonder_docs(op, version)
+            }),
         )
         .api_route(
             "/youngest",
-            get_with(get_youngest_wonder, get_youngest_wonder_docs),
+            get_with(get_youngest_wonder, move |op| {
+                get_youngest_wonder_docs(op, version)
+            }),
         )
         .api_route(
             "/name/:name",
-            get_with(get_wonder_by_name, get_wonder_by_name_docs),
+            get_with(get_wonder_by_name, move |op| {
+                get_wonder_by_name_docs(op, version)
+            }),
+        )
+        .api_route(
+            "/name/:name/related",
+            get_with(get_related_wonders, move |op| {
+                get_related_wonders_docs(op, version)
+            }),
+        )
+        // Only applies to the routes above, as their responses only change along with the dataset
+        .layer(middleware::from_fn_with_state(
+            CacheHeaders::new(cache_config),
+            conditional_get,
+        ))
+        .api_route(
+            "/random",
+            get_with(get_random_wonder, move |op| {
+                get_random_wonder_docs(op, version)
+            }),
+        )
+        // Not cached, as the current date may change before the cached response expires
+        .api_route(
+            "/daily",
+            get_with(get_daily_wonder, move |op| {
+                get_daily_wonder_docs(op, version)
+            }),
         )
 }
 
 // UTILS ------------------------------------------------------------------------------------------
-/// Filters wonders based on given [`WondersParams`]
-fn filter_wonders(wonders: &mut Vec<&'static Wonder>, params: WonderParamsFiltering) -> Result<()> {
-    if let Some(name) = params.name.as_deref() {
-        wonders.retain(|w| w.name.to_lowercase().contains(&name.to_lowercase()));
+/// Filters wonders based on given [`WonderParamsFiltering`], returning the indexes of the
+/// remaining wonders (see [`crate::data::WonderIndex`]) in dataset order
+pub fn filter_wonders(params: WonderParamsFiltering) -> Result<Vec<usize>> {
+    usage_metrics::record_filters(&params);
+
+    // Start from the smallest set of wonders the index allows
+    let mut wonders: Vec<usize> = match (params.category.as_ref(), params.time_period.as_ref()) {
+        (Some(category), _) => INDEX.by_category.get(category).cloned().unwrap_or_default(),
+        (None, Some(time_period)) => INDEX
+            .by_time_period
+            .get(time_period)
+            .cloned()
+            .unwrap_or_default(),
+        (None, None) => (0..WONDERS.len()).collect(),
     };
-    if let Some(location) = params.location.as_deref() {
-        wonders.retain(|w| w.location.to_lowercase().contains(&location.to_lowercase()));
+
+    if let (Some(_), Some(time_period)) = (params.category.as_ref(), params.time_period.as_ref()) {
+        wonders.retain(|&i| WONDERS[i].time_period == *time_period);
     };
-    if let Some(time_period) = params.time_period.as_ref() {
-        wonders.retain(|w| w.time_period == *time_period);
+    if let Some(name) = params.name.as_deref() {
+        let name = name.to_lowercase();
+        wonders.retain(|&i| INDEX.names_lowercase[i].contains(&name));
     };
-    if let Some(category) = params.category.as_ref() {
-        wonders.retain(|w| w.categories.contains(category));
+    if let Some(location) = params.location.as_deref() {
+        let location = location.to_lowercase();
+        wonders.retain(|&i| INDEX.locations_lowercase[i].contains(&location));
     };
 
     // Handle upper and lower limits for build year
@@ -108,10 +666,10 @@ fn filter_wonders(wonders: &mut Vec<&'static Wonder>, params: WonderParamsFilter
             if l > u {
                 return Err(Error::ConflictingLimitParams(*l, *u));
             };
-            wonders.retain(|w| w.build_year >= *l && w.build_year <= *u);
+            wonders.retain(|&i| WONDERS[i].build_year >= *l && WONDERS[i].build_year <= *u);
         }
-        (Some(l), None) => wonders.retain(|w| w.build_year >= *l),
-        (None, Some(u)) => wonders.retain(|w| w.build_year <= *u),
+        (Some(l), None) => wonders.retain(|&i| WONDERS[i].build_year >= *l),
+        (None, Some(u)) => wonders.retain(|&i| WONDERS[i].build_year <= *u),
         (None, None) => {}
     };
 
@@ -119,31 +677,30 @@ fn filter_wonders(wonders: &mut Vec<&'static Wonder>, params: WonderParamsFilter
         return Err(Error::NoWondersLeft);
     }
 
-    Ok(())
+    Ok(wonders)
 }
 
-/// Filters wonders based on given [`WondersParams`], but ignores the no wonders error.
+/// Filters wonders based on given [`WonderParamsFiltering`], but ignores the no wonders error.
 ///
 /// Intended for endpoints which should instead return an empty vec
-fn filter_wonders_ignore_empty(
-    wonders: &mut Vec<&'static Wonder>,
-    params: WonderParamsFiltering,
-) -> Result<()> {
-    let res = filter_wonders(wonders, params);
-    // Ignore no wonders error, just continue with the empty vec
-    if matches!(res, Err(Error::NoWondersLeft)) {
-        Ok(())
-    } else {
-        res
+pub fn filter_wonders_ignore_empty(params: WonderParamsFiltering) -> Result<Vec<usize>> {
+    match filter_wonders(params) {
+        // Ignore no wonders error, just continue with the empty vec
+        Err(Error::NoWondersLeft) => Ok(Vec::new()),
+        res => res,
     }
 }
 
-/// Sorts wonders based on given [`WondersParams`]
-fn sort_wonders(wonders: &mut [&'static Wonder], params: WonderParamsSorting) {
+/// Sorts wonders based on given [`WonderParamsSorting`]
+pub fn sort_wonders(wonders: &mut [usize], params: WonderParamsSorting) {
+    usage_metrics::record_sorting(&params);
+
     if let Some(sort_by) = params.sort_by.as_ref() {
+        // Ranks are precomputed with a stable sort, so this is equivalent to sorting by the
+        // actual values
         match sort_by {
-            SortBy::Alphabetical => wonders.sort_by(|a, b| a.name.cmp(&b.name)),
-            SortBy::BuildYear => wonders.sort_by_key(|w| w.build_year),
+            SortBy::Alphabetical => wonders.sort_unstable_by_key(|&i| INDEX.alphabetical_rank[i]),
+            SortBy::BuildYear => wonders.sort_unstable_by_key(|&i| INDEX.build_year_rank[i]),
         }
 
         // `sort_reverse` only matters when `sort_by` is provided, and `sort_reverse = true`
@@ -153,80 +710,770 @@ fn sort_wonders(wonders: &mut [&'static Wonder], params: WonderParamsSorting) {
     };
 }
 
+/// Build a JSON array response from the pre-serialised wonders
+pub fn wonders_response(wonders: &[usize]) -> Response {
+    let len = wonders
+        .iter()
+        .map(|&i| INDEX.json[i].len() + 1)
+        .sum::<usize>()
+        + 2;
+    let mut body = Vec::with_capacity(len);
+
+    body.push(b'[');
+    for (n, &i) in wonders.iter().enumerate() {
+        if n > 0 {
+            body.push(b',');
+        }
+        body.extend_from_slice(&INDEX.json[i]);
+    }
+    body.push(b']');
+
+    json_response(Bytes::from(body))
+}
+
+/// Build a JSON object response mapping each group to its pre-serialised wonders
+pub fn grouped_wonders_response(groups: &[(String, Vec<usize>)]) -> Response {
+    let mut body = vec![b'{'];
+    for (n, (key, wonders)) in groups.iter().enumerate() {
+        if n > 0 {
+            body.push(b',');
+        }
+        body.extend_from_slice(&serde_json::to_vec(key).expect("Failed to serialise group key"));
+        body.push(b':');
+        body.push(b'[');
+        for (n, &i) in wonders.iter().enumerate() {
+            if n > 0 {
+                body.push(b',');
+            }
+            body.extend_from_slice(&INDEX.json[i]);
+        }
+        body.push(b']');
+    }
+    body.push(b'}');
+
+    json_response(Bytes::from(body))
+}
+
+/// Build a JSON response from a single pre-serialised wonder
+pub fn wonder_response(wonder: usize) -> Response {
+    json_response(INDEX.json[wonder].clone())
+}
+
+fn json_response(body: Bytes) -> Response {
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+/// Gets all wonder categories, optionally excluding those related to video games
+pub(crate) fn get_categories(exclude_games: Option<bool>) -> Vec<Category> {
+    Category::iter()
+        // Filter out game categories :(
+        .filter(|c| {
+            !matches!(
+                (exclude_games, c),
+                (Some(true), Category::Civ5 | Category::Civ6)
+            )
+        })
+        .collect()
+}
+
+/// Finds the wonder matching the given name, parsed as lowercase letters with spaces replaced
+/// with '-'
+pub fn find_wonder_by_name(name: &str) -> Option<usize> {
+    let wonder = INDEX.by_slug.get(name).copied();
+    // Only slugs of existing wonders are recorded, to keep the metric's cardinality bounded
+    if wonder.is_some() {
+        usage_metrics::record_lookup(name);
+    }
+    wonder
+}
+
+/// Gets the oldest (least recently built) of the given wonders
+pub(crate) fn oldest_wonder(wonders: &[usize]) -> Option<usize> {
+    wonders.iter().copied().reduce(|a, b| {
+        if WONDERS[a].build_year < WONDERS[b].build_year {
+            a
+        } else {
+            b
+        }
+    })
+}
+
+/// Gets the youngest (most recently built) of the given wonders
+pub(crate) fn youngest_wonder(wonders: &[usize]) -> Option<usize> {
+    wonders.iter().copied().reduce(|a, b| {
+        if WONDERS[a].build_year > WONDERS[b].build_year {
+            a
+        } else {
+            b
+        }
+    })
+}
+
+/// Groups the given wonders, keeping their order within each group. Groups are in chronological
+/// order for time periods and centuries, and in declaration order otherwise, with empty groups
+/// omitted
+pub fn group_wonders(wonders: &[usize], group_by: GroupBy) -> Vec<(String, Vec<usize>)> {
+    fn group_by_key<K: Ord>(
+        wonders: &[usize],
+        keys: impl Fn(usize) -> Vec<K>,
+    ) -> BTreeMap<K, Vec<usize>> {
+        let mut groups: BTreeMap<K, Vec<usize>> = BTreeMap::new();
+        for &i in wonders {
+            for key in keys(i) {
+                groups.entry(key).or_default().push(i);
+            }
+        }
+        groups
+    }
+    fn variant_name(value: impl Serialize) -> String {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(name)) => name,
+            _ => unreachable!("Unit variants serialise as strings"),
+        }
+    }
+
+    match group_by {
+        GroupBy::TimePeriod => group_by_key(wonders, |i| vec![WONDERS[i].time_period])
+            .into_iter()
+            .map(|(key, wonders)| (variant_name(key), wonders))
+            .collect(),
+        GroupBy::Category => group_by_key(wonders, |i| WONDERS[i].categories.clone())
+            .into_iter()
+            .map(|(key, wonders)| (variant_name(key), wonders))
+            .collect(),
+        GroupBy::Continent => group_by_key(wonders, |i| vec![INDEX.continents[i]])
+            .into_iter()
+            .map(|(key, wonders)| (variant_name(key), wonders))
+            .collect(),
+        GroupBy::Century => group_by_key(wonders, |i| {
+            vec![period(WONDERS[i].build_year, Resolution::Century)]
+        })
+        .into_iter()
+        .map(|(key, wonders)| (key.label, wonders))
+        .collect(),
+    }
+}
+
+/// Period of the given resolution containing the given year. There is no year 0, so it's treated
+/// as 1 BCE
+pub fn period(year: i16, resolution: Resolution) -> Period {
+    let year = astronomical_year(year);
+    let bce = year <= 0;
+    let (era, n) = if bce { ("BCE", 1 - year) } else { ("CE", year) };
+
+    // First and last years of the period, counting away from year 0
+    let (first, last, label) = match resolution {
+        Resolution::Decade => {
+            let decade = n - n % 10;
+            (decade.max(1), decade + 9, format!("{decade}s {era}"))
+        }
+        Resolution::Century | Resolution::Millennium => {
+            let (size, name) = match resolution {
+                Resolution::Millennium => (1000, "millennium"),
+                _ => (100, "century"),
+            };
+            let ordinal = (n - 1) / size + 1;
+            let suffix = match (ordinal % 10, ordinal % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            (
+                (ordinal - 1) * size + 1,
+                ordinal * size,
+                format!("{ordinal}{suffix} {name} {era}"),
+            )
+        }
+    };
+
+    if bce {
+        Period {
+            start: -last,
+            end: -first,
+            label,
+        }
+    } else {
+        Period {
+            start: first,
+            end: last,
+            label,
+        }
+    }
+}
+
+/// Split the given wonders into periods of the given resolution, in chronological order with
+/// wonders sorted by build year. Empty periods are omitted
+pub fn timeline(wonders: &[usize], resolution: Resolution) -> Vec<TimelineBucket<usize>> {
+    let mut wonders = wonders.to_vec();
+    wonders.sort_unstable_by_key(|&i| INDEX.build_year_rank[i]);
+
+    let mut buckets: Vec<TimelineBucket<usize>> = Vec::new();
+    for i in wonders {
+        let period = period(WONDERS[i].build_year, resolution);
+        match buckets.last_mut() {
+            Some(bucket) if bucket.start == period.start => bucket.wonders.push(i),
+            _ => buckets.push(TimelineBucket {
+                label: period.label,
+                start: period.start,
+                end: period.end,
+                wonders: vec![i],
+            }),
+        }
+    }
+    buckets
+}
+
+/// Compare two wonders
+pub fn compare_wonders(a: usize, b: usize) -> WonderComparison<usize> {
+    let (wonder_a, wonder_b) = (&WONDERS[a], &WONDERS[b]);
+    let age_difference_years = years_between(wonder_a.build_year, wonder_b.build_year);
+
+    WonderComparison {
+        a,
+        b,
+        age_difference_years,
+        built_close_together: age_difference_years <= BUILT_CLOSE_TOGETHER_YEARS,
+        distance_km: match (INDEX.coordinates[a], INDEX.coordinates[b]) {
+            // Rounded to the nearest metre
+            (Some(a), Some(b)) => Some((a.distance_km(&b) * 1000.0).round() / 1000.0),
+            _ => None,
+        },
+        shared_categories: wonder_a
+            .categories
+            .iter()
+            .filter(|category| wonder_b.categories.contains(category))
+            .copied()
+            .collect(),
+        same_time_period: wonder_a.time_period == wonder_b.time_period,
+    }
+}
+
+/// Number of years between two years. There is no year 0, so one year is skipped between years
+/// BCE and CE
+fn years_between(a: i16, b: i16) -> u16 {
+    // Always fits, as `i16` years are at most 65534 years apart once year 0 is skipped
+    astronomical_year(a).abs_diff(astronomical_year(b)) as u16
+}
+
+/// Convert a year, where negative years are BCE, to astronomical year numbering, where 1 BCE is
+/// year 0. There is no year 0 in the former, so it's treated as 1 BCE
+fn astronomical_year(year: i16) -> i32 {
+    match i32::from(year) {
+        year if year <= 0 => year.min(-1) + 1,
+        year => year,
+    }
+}
+
+/// Score how closely every other wonder is related to the given wonder, returning the `limit`
+/// most closely related wonders, best first
+pub fn related_wonders(wonder: usize, limit: usize) -> Vec<RelatedWonder<usize>> {
+    let time_periods: Vec<TimePeriod> = TimePeriod::iter().collect();
+    let time_period_position = |i: usize| {
+        time_periods
+            .iter()
+            .position(|&t| t == WONDERS[i].time_period)
+            .unwrap()
+    };
+    let round = |score: f64| (score * 1000.0).round() / 1000.0;
+
+    let mut related: Vec<RelatedWonder<usize>> = (0..WONDERS.len())
+        .filter(|&i| i != wonder)
+        .map(|i| {
+            let (a, b) = (&WONDERS[wonder].categories, &WONDERS[i].categories);
+            let shared = a.iter().filter(|c| b.contains(c)).count();
+            let combined = a.len() + b.len() - shared;
+
+            let breakdown = RelatedScores {
+                categories: if combined == 0 {
+                    0.0
+                } else {
+                    shared as f64 / combined as f64
+                },
+                time_period: 1.0
+                    - time_period_position(wonder).abs_diff(time_period_position(i)) as f64
+                        / (time_periods.len() - 1) as f64,
+                distance: match (INDEX.coordinates[wonder], INDEX.coordinates[i]) {
+                    (Some(a), Some(b)) => (-a.distance_km(&b) / RELATED_DISTANCE_KM).exp(),
+                    _ => 0.0,
+                },
+                summary: INDEX.summary_terms[wonder].similarity(&INDEX.summary_terms[i]),
+            };
+            RelatedWonder {
+                wonder: i,
+                score: breakdown.average(),
+                breakdown,
+            }
+        })
+        .collect();
+
+    // Stable, so ties are kept in dataset order
+    related.sort_by(|a, b| b.score.total_cmp(&a.score));
+    related.truncate(limit);
+    for related in &mut related {
+        related.score = round(related.score);
+        let breakdown = &mut related.breakdown;
+        for score in [
+            &mut breakdown.categories,
+            &mut breakdown.time_period,
+            &mut breakdown.distance,
+            &mut breakdown.summary,
+        ] {
+            *score = round(*score);
+        }
+    }
+    related
+}
+
+/// Compute aggregate statistics about the given wonders, with a build year histogram using
+/// buckets of the given width
+pub fn wonder_stats(wonders: &[usize], bucket_width: u16) -> WonderStats {
+    let mut by_category: BTreeMap<Category, usize> = Category::iter().map(|c| (c, 0)).collect();
+    let mut by_time_period: BTreeMap<TimePeriod, usize> =
+        TimePeriod::iter().map(|t| (t, 0)).collect();
+    let mut by_continent: BTreeMap<Continent, usize> = Continent::iter().map(|c| (c, 0)).collect();
+    for &i in wonders {
+        for category in &WONDERS[i].categories {
+            *by_category.entry(*category).or_default() += 1;
+        }
+        *by_time_period.entry(WONDERS[i].time_period).or_default() += 1;
+        *by_continent.entry(INDEX.continents[i]).or_default() += 1;
+    }
+
+    let mut build_years: Vec<i16> = wonders.iter().map(|&i| WONDERS[i].build_year).collect();
+    build_years.sort_unstable();
+    let build_year = build_year_stats(&build_years);
+
+    // Buckets are aligned to multiples of the width, with empty buckets between wonders included
+    let width = i32::from(bucket_width);
+    let bucket_start = |year: i16| i32::from(year).div_euclid(width) * width;
+    let build_year_histogram = match (build_years.first(), build_years.last()) {
+        (Some(&min), Some(&max)) => {
+            let first = bucket_start(min);
+            let mut buckets: Vec<HistogramBucket> = (first..=bucket_start(max))
+                .step_by(bucket_width.into())
+                .map(|start| HistogramBucket {
+                    start,
+                    end: start + width,
+                    count: 0,
+                })
+                .collect();
+            for &year in &build_years {
+                buckets[((bucket_start(year) - first) / width) as usize].count += 1;
+            }
+            buckets
+        }
+        _ => Vec::new(),
+    };
+
+    WonderStats {
+        count: wonders.len(),
+        by_category,
+        by_time_period,
+        by_continent,
+        build_year,
+        build_year_histogram,
+    }
+}
+
+/// Summarise the given build years, which must be sorted
+fn build_year_stats(build_years: &[i16]) -> Option<BuildYearStats> {
+    let (&min, &max) = (build_years.first()?, build_years.last()?);
+    let mid = build_years.len() / 2;
+    let median = if build_years.len().is_multiple_of(2) {
+        (f64::from(build_years[mid - 1]) + f64::from(build_years[mid])) / 2.0
+    } else {
+        f64::from(build_years[mid])
+    };
+    let mean = build_years.iter().map(|&y| f64::from(y)).sum::<f64>() / build_years.len() as f64;
+
+    Some(BuildYearStats {
+        min,
+        max,
+        median,
+        mean,
+    })
+}
+
+/// Select `count` distinct wonders at random, in random order, from the given wonders. Selections
+/// using the same seed are reproducible
+pub fn random_wonders(
+    wonders: &[usize],
+    count: usize,
+    seed: Option<u64>,
+    weights: &RandomWeights,
+) -> Result<Vec<usize>> {
+    fn select(
+        wonders: &[usize],
+        count: usize,
+        weights: &RandomWeights,
+        rng: &mut impl Rng,
+    ) -> Vec<usize> {
+        let mut wonders = wonders.to_vec();
+        weighted_shuffle(&mut wonders, weights, rng);
+        wonders.truncate(count);
+        wonders
+    }
+
+    if count > wonders.len() {
+        return Err(Error::NotEnoughWonders(count, wonders.len()));
+    }
+
+    Ok(match seed {
+        // Unlike `StdRng`, the algorithm is guaranteed to stay the same, so seeds remain
+        // reproducible
+        Some(seed) => select(
+            wonders,
+            count,
+            weights,
+            &mut Xoshiro256PlusPlus::seed_from_u64(seed),
+        ),
+        None => select(wonders, count, weights, &mut rand::rng()),
+    })
+}
+
+/// Shuffle the wonders so those with greater weights are more likely to come first, using the
+/// algorithm by Efraimidis and Spirakis
+fn weighted_shuffle(wonders: &mut [usize], weights: &RandomWeights, rng: &mut impl Rng) {
+    if weights.is_uniform() {
+        wonders.shuffle(rng);
+        return;
+    }
+
+    // Sorting by `u^(1/weight)` for uniformly random `u` in (0, 1], compared as logarithms for
+    // precision
+    let mut keys: Vec<(f64, usize)> = wonders
+        .iter()
+        .map(|&i| ((1.0 - rng.random::<f64>()).ln() / weights.weight(i), i))
+        .collect();
+    keys.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+    for (wonder, (_, i)) in wonders.iter_mut().zip(keys) {
+        *wonder = i;
+    }
+}
+
+/// Wonder of the day for the given date, from the given wonders in dataset order. Only depends on
+/// its inputs, so every instance of the API selects the same wonder
+pub fn daily_wonder(wonders: &[usize], date: NaiveDate) -> usize {
+    let day = (date - NaiveDate::default()).num_days();
+    shuffled_sequence(wonders, DAILY_SEED, day, &RandomWeights::default())
+}
+
+/// Wonder at the given index of an endless sequence of the given wonders, made up of cycles in
+/// which each wonder appears once, shuffled based on the seed and weights. With more than two
+/// wonders, the same wonder never appears twice in a row
+pub fn shuffled_sequence(
+    wonders: &[usize],
+    seed: u64,
+    index: i64,
+    weights: &RandomWeights,
+) -> usize {
+    let len = wonders.len() as i64;
+    let (cycle, position) = (index.div_euclid(len), index.rem_euclid(len) as usize);
+
+    let mut order = cycle_order(wonders, seed, cycle, weights);
+    // Avoid the same wonder at the end of one cycle and the start of the next
+    if order.len() > 2 && order.first() == cycle_order(wonders, seed, cycle - 1, weights).last() {
+        order.swap(0, 1);
+    }
+    order[position]
+}
+
+/// Order of the wonders during the given cycle of [`shuffled_sequence`]
+fn cycle_order(wonders: &[usize], seed: u64, cycle: i64, weights: &RandomWeights) -> Vec<usize> {
+    let mut order = wonders.to_vec();
+    weighted_shuffle(&mut order, weights, &mut sequence_rng(seed, cycle));
+    order
+}
+
+/// Wonder at the given index of a [`ShuffleSession`] favouring unseen wonders, where each wonder
+/// is drawn independently, with those not in `seen` being favoured
+fn unseen_favouring_draw(
+    wonders: &[usize],
+    seed: u64,
+    index: i64,
+    weights: &RandomWeights,
+    seen: &[usize],
+) -> usize {
+    *wonders
+        .choose_weighted(&mut sequence_rng(seed, index), |&wonder| {
+            if seen.contains(&wonder) {
+                weights.weight(wonder)
+            } else {
+                weights.weight(wonder) * FAVOURED_WEIGHT
+            }
+        })
+        .expect("Wonders are never empty, and weights are always positive")
+}
+
+/// Random number generator for the given position in a sequence of random wonders
+fn sequence_rng(seed: u64, position: i64) -> Xoshiro256PlusPlus {
+    // Spreads consecutive positions across the seeds
+    let seed = seed ^ (position as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    // Unlike `StdRng`, the algorithm is guaranteed to stay the same, so the sequence does too
+    Xoshiro256PlusPlus::seed_from_u64(seed)
+}
+
+/// Parse the date requested by the given parameters, defaulting to the date at `now` in the given
+/// time zone
+fn requested_date(params: &DailyParams, now: DateTime<Utc>) -> Result<NaiveDate> {
+    if let Some(date) = params.date.as_deref() {
+        return NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            Error::InvalidRequest(format!("Invalid date '{date}', expected YYYY-MM-DD"))
+        });
+    }
+
+    let Some(tz) = params.tz.as_deref() else {
+        return Ok(now.date_naive());
+    };
+    if let Ok(tz) = tz.parse::<Tz>() {
+        return Ok(now.with_timezone(&tz).date_naive());
+    }
+    tz.parse::<FixedOffset>()
+        .map(|offset| now.with_timezone(&offset).date_naive())
+        .map_err(|_| {
+            Error::InvalidRequest(format!(
+                "Invalid time zone '{tz}', expected an IANA time zone such as Europe/Oslo, or a \
+                UTC offset such as -08:00"
+            ))
+        })
+}
+
 // HANDLERS ----------------------------------------------------------------------------------------
 // GET ALL WONDERS
 async fn get_all_wonders(
     Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
     Garde(Query(sorting_params)): Garde<Query<WonderParamsSorting>>,
+    Garde(Query(grouping_params)): Garde<Query<GroupingParams>>,
 ) -> impl IntoApiResponse {
-    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
+    let mut wonders = match filter_wonders_ignore_empty(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
+    };
+    sort_wonders(&mut wonders, sorting_params);
 
-    if let Err(e) = filter_wonders_ignore_empty(&mut wonders, filtering_params) {
-        return e.into_response();
+    match grouping_params.group_by {
+        Some(group_by) => grouped_wonders_response(&group_wonders(&wonders, group_by)),
+        None => wonders_response(&wonders),
+    }
+}
+fn get_all_wonders_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("All wonders")
+        .description(
+            "Get all wonders after applying filters and sort methods defined by query parameters. \
+            If `group_by` is provided, an object mapping each group to its wonders is returned \
+            instead",
+        )
+        .response_with::<200, Json<MaybeGrouped<Vec<&'static Wonder>>>, _>(|res| {
+            res.example(MaybeGrouped::Ungrouped(vec![&WONDERS[0]]))
+        })
+        .with(|op| error_docs(op, version, Error::ConflictingLimitParams(1000, 400)))
+}
+
+// GET ALL WONDERS - PAGINATED
+async fn get_all_wonders_paginated(
+    uri: Uri,
+    Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(sorting_params)): Garde<Query<WonderParamsSorting>>,
+    Garde(Query(pagination_params)): Garde<Query<PaginationParams>>,
+) -> impl IntoApiResponse {
+    // Checked without being a documented parameter, as it's only rejected
+    #[derive(Deserialize)]
+    struct UnsupportedParams {
+        group_by: Option<String>,
+    }
+    if axum::extract::Query::<UnsupportedParams>::try_from_uri(&uri)
+        .is_ok_and(|query| query.0.group_by.is_some())
+    {
+        return Error::InvalidRequest(GROUP_BY_UNSUPPORTED.to_string()).into_response();
+    }
+
+    let mut wonders = match filter_wonders_ignore_empty(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
     };
     sort_wonders(&mut wonders, sorting_params);
 
-    Json(wonders).into_response()
+    Json(Page::new(&wonders, &pagination_params).map(|i| &WONDERS[i])).into_response()
 }
-fn get_all_wonders_docs(op: TransformOperation) -> TransformOperation {
+fn get_all_wonders_paginated_docs(op: TransformOperation) -> TransformOperation {
     op.summary("All wonders")
         .description(
-            "Get all wonders after applying filters and sort methods defined by query parameters",
+            "Get a page of all wonders after applying filters and sort methods defined by query \
+            parameters. Grouping using `group_by` is not supported, and gives a `400` response",
         )
-        .response_with::<200, Json<Vec<&'static Wonder>>, _>(|res| res.example(vec![&WONDERS[0]]))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::ConflictingLimitParams(1000, 400)))
+        .response_with::<200, Json<Page<&'static Wonder>>, _>(|res| {
+            res.example(Page {
+                items: vec![&WONDERS[0]],
+                page: 1,
+                per_page: 1,
+                total: WONDERS.len(),
+                total_pages: WONDERS.len(),
+            })
+        })
+        .with(|op| {
+            errors_docs(
+                op,
+                ApiVersion::V1,
+                &[
+                    Error::ConflictingLimitParams(1000, 400),
+                    Error::InvalidRequest(GROUP_BY_UNSUPPORTED.to_string()),
+                ],
+            )
         })
 }
 
 // GET NUM WONDERS
 async fn get_count_wonders(
     Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(grouping_params)): Garde<Query<GroupingParams>>,
 ) -> impl IntoApiResponse {
-    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-    if let Err(e) = filter_wonders_ignore_empty(&mut wonders, filtering_params) {
-        return e.into_response();
+    let wonders = match filter_wonders_ignore_empty(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
     };
-    Json(wonders.len()).into_response()
+
+    match grouping_params.group_by {
+        Some(group_by) => {
+            let counts: serde_json::Map<String, serde_json::Value> =
+                group_wonders(&wonders, group_by)
+                    .into_iter()
+                    .map(|(key, wonders)| (key, wonders.len().into()))
+                    .collect();
+            Json(counts).into_response()
+        }
+        None => Json(wonders.len()).into_response(),
+    }
 }
-fn get_count_wonders_docs(op: TransformOperation) -> TransformOperation {
+fn get_count_wonders_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
     op.summary("Number of wonders")
         .description(
-            "Get the total number of wonders, after applying filters defined by query parameters",
+            "Get the total number of wonders, after applying filters defined by query parameters. \
+            If `group_by` is provided, an object mapping each group to its number of wonders is \
+            returned instead",
         )
-        .response_with::<200, Json<usize>, _>(|res| res.example(WONDERS.len()))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::ConflictingLimitParams(1000, 400)))
+        .response_with::<200, Json<MaybeGrouped<usize>>, _>(|res| {
+            res.example(MaybeGrouped::Ungrouped(WONDERS.len()))
         })
+        .with(|op| error_docs(op, version, Error::ConflictingLimitParams(1000, 400)))
 }
 
-// GET WONDER CATEGORIES
-async fn get_wonder_categories(Query(params): Query<CategoriesParams>) -> impl IntoApiResponse {
-    let categories: Vec<Category> = Category::iter()
-        // Filter out game categories :(
-        .filter(|c| {
-            !matches!(
-                (params.exclude_games, c),
-                (Some(true), Category::Civ5 | Category::Civ6)
+// GET WONDER STATS
+async fn get_wonder_stats(
+    Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(stats_params)): Garde<Query<StatsParams>>,
+) -> impl IntoApiResponse {
+    match filter_wonders_ignore_empty(filtering_params) {
+        Ok(wonders) => Json(wonder_stats(
+            &wonders,
+            stats_params.bucket_width.unwrap_or(DEFAULT_BUCKET_WIDTH),
+        ))
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+fn get_wonder_stats_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+    op.summary("Wonder statistics")
+        .description(
+            "Get the number of wonders in each category, time period and continent, along with \
+            build year statistics and a histogram of build years, after applying filters defined \
+            by query parameters",
+        )
+        .response_with::<200, Json<WonderStats>, _>(|res| {
+            res.example(wonder_stats(&wonders, DEFAULT_BUCKET_WIDTH))
+        })
+        .with(|op| error_docs(op, version, Error::ConflictingLimitParams(1000, 400)))
+}
+
+// GET WONDER TIMELINE
+async fn get_wonder_timeline(
+    Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(timeline_params)): Garde<Query<TimelineParams>>,
+) -> impl IntoApiResponse {
+    let wonders = match filter_wonders_ignore_empty(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
+    };
+    let resolution = timeline_params.resolution.unwrap_or(Resolution::Century);
+
+    Json(
+        timeline(&wonders, resolution)
+            .into_iter()
+            .map(|bucket| bucket.map(|i| &WONDERS[i]))
+            .collect::<Vec<_>>(),
+    )
+    .into_response()
+}
+fn get_wonder_timeline_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("Wonder timeline")
+        .description(
+            "Get wonders split into decades, centuries or millennia in chronological order, after \
+            applying filters defined by query parameters. Periods without any wonders are omitted",
+        )
+        .response_with::<200, Json<Vec<TimelineBucket<&'static Wonder>>>, _>(|res| {
+            res.example(
+                timeline(&[0], Resolution::Century)
+                    .into_iter()
+                    .map(|bucket| bucket.map(|i| &WONDERS[i]))
+                    .collect::<Vec<_>>(),
             )
         })
-        .collect();
+        .with(|op| error_docs(op, version, Error::ConflictingLimitParams(1000, 400)))
+}
 
-    Json(categories).into_response()
+// COMPARE WONDERS
+async fn get_wonder_comparison(
+    Garde(Query(params)): Garde<Query<CompareParams>>,
+) -> impl IntoApiResponse {
+    let Some(a) = find_wonder_by_name(&params.a) else {
+        return Error::NoMatchingName(params.a).into_response();
+    };
+    let Some(b) = find_wonder_by_name(&params.b) else {
+        return Error::NoMatchingName(params.b).into_response();
+    };
+
+    Json(compare_wonders(a, b).map(|i| &WONDERS[i])).into_response()
 }
-fn get_wonder_categories_docs(op: TransformOperation) -> TransformOperation {
+fn get_wonder_comparison_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("Compare wonders")
+        .description(&format!(
+            "Compare two wonders, given by name as lowercase letters with spaces replaced with \
+            '-'. Includes the difference between their build years, whether they were built at \
+            most {BUILT_CLOSE_TOGETHER_YEARS} years apart, the distance between them and the \
+            categories they share. As it isn't known when wonders were destroyed, whether they \
+            ever stood at the same time isn't included",
+        ))
+        .response_with::<200, Json<WonderComparison<&'static Wonder>>, _>(|res| {
+            res.example(compare_wonders(0, 1).map(|i| &WONDERS[i]))
+        })
+        .with(|op| error_docs(op, version, Error::NoMatchingName("...".to_owned())))
+}
+
+// GET WONDER CATEGORIES
+async fn get_wonder_categories(Query(params): Query<CategoriesParams>) -> impl IntoApiResponse {
+    Json(get_categories(params.exclude_games)).into_response()
+}
+fn get_wonder_categories_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
     op.summary("Wonder categories")
         .description("Get all available wonder categories")
         .response_with::<200, Json<Vec<Category>>, _>(|res| {
             res.example(Category::iter().collect::<Vec<Category>>())
         })
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::InvalidRequest(
-                    "Failed to deserialize query string".to_string(),
-                )))
+        .with(|op| {
+            error_docs(
+                op,
+                version,
+                Error::InvalidRequest("Failed to deserialize query string".to_string()),
+            )
         })
 }
 
@@ -256,53 +1503,165 @@ fn get_wonder_sort_by_docs(op: TransformOperation) -> TransformOperation {
 
 // GET WONDER BY NAME
 async fn get_wonder_by_name(Path(name): Path<String>) -> impl IntoApiResponse {
-    let Some(wonder) = WONDERS
-        .iter()
-        .find(|w| w.name.to_ascii_lowercase().replace(' ', "-") == name)
-    else {
+    let Some(wonder) = find_wonder_by_name(&name) else {
+        return Error::NoMatchingName(name).into_response();
+    };
+
+    wonder_response(wonder)
+}
+fn get_wonder_by_name_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("Specific wonder - by name")
+        .description(
+            "Get a specific wonder matching the name defined by the path. Note that
+the name will be parsed as lowercase letters with spaces replaced with '-'.",
+        )
+        .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[1]))
+        .with(|op| error_docs(op, version, Error::NoMatchingName("...".to_owned())))
+}
+
+// GET RELATED WONDERS
+async fn get_related_wonders(
+    Path(name): Path<String>,
+    Garde(Query(params)): Garde<Query<RelatedParams>>,
+) -> impl IntoApiResponse {
+    let Some(wonder) = find_wonder_by_name(&name) else {
         return Error::NoMatchingName(name).into_response();
     };
 
-    Json(wonder).into_response()
+    Json(
+        related_wonders(wonder, params.limit.unwrap_or(DEFAULT_RELATED))
+            .into_iter()
+            .map(|related| related.map(|i| &WONDERS[i]))
+            .collect::<Vec<_>>(),
+    )
+    .into_response()
 }
-fn get_wonder_by_name_docs(op: TransformOperation) -> TransformOperation {
-    op.summary("Specific wonder - by name")
+fn get_related_wonders_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("Related wonders")
         .description(
-            "Get a specific wonder matching the name defined by the path. Note that
-the name will be parsed as lowercase letters with spaces replaced with '-'.",
+            "Get the wonders most closely related to the wonder matching the name defined by the \
+            path, best first. Wonders are scored by the categories they share, how close their \
+            time periods are, the distance between them and how similar their summaries are, \
+            with the scores for each included",
         )
-        .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[1]))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::NoMatchingName("...".to_owned())))
+        .response_with::<200, Json<Vec<RelatedWonder<&'static Wonder>>>, _>(|res| {
+            res.example(
+                related_wonders(0, 1)
+                    .into_iter()
+                    .map(|related| related.map(|i| &WONDERS[i]))
+                    .collect::<Vec<_>>(),
+            )
         })
+        .with(|op| error_docs(op, version, Error::NoMatchingName("...".to_owned())))
 }
 
 // GET RANDOM WONDER
 async fn get_random_wonder(
     Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(random_params)): Garde<Query<RandomParams>>,
 ) -> impl IntoApiResponse {
     assert!(!WONDERS.is_empty());
 
-    let mut rng = rand::rng();
+    let count = random_params.count.unwrap_or(1);
+    let weights = RandomWeights::new(&random_params);
+    if weights.unseen && random_params.session.is_none() {
+        return Error::InvalidRequest(
+            "`favour_unseen` can only be used along with a shuffle `session`".to_string(),
+        )
+        .into_response();
+    }
+    let mut session = match random_params.session.as_deref() {
+        None => None,
+        Some(NEW_SESSION) => Some(ShuffleSession::new(
+            random_params.seed.unwrap_or_else(|| rand::rng().random()),
+        )),
+        Some(token) => match token.parse::<ShuffleSession>() {
+            Ok(session) => Some(session),
+            Err(e) => return e.into_response(),
+        },
+    };
+    let wonders = match filter_wonders(filtering_params).and_then(|wonders| match &mut session {
+        Some(session) => session.take(&wonders, count, &weights),
+        None => random_wonders(&wonders, count, random_params.seed, &weights),
+    }) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
+    };
 
-    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-    if let Err(e) = filter_wonders(&mut wonders, filtering_params) {
-        return e.into_response();
+    let mut response = match random_params.count {
+        Some(_) => wonders_response(&wonders),
+        None => wonder_response(wonders[0]),
     };
+    if let Some(session) = session {
+        response.headers_mut().insert(
+            SHUFFLE_SESSION_HEADER.clone(),
+            HeaderValue::from_str(&session.to_string()).unwrap(),
+        );
+    }
 
-    Json(wonders.choose(&mut rng).unwrap()).into_response()
+    // Random responses shouldn't be cached
+    let random = random_params.seed.is_none()
+        && matches!(random_params.session.as_deref(), None | Some(NEW_SESSION));
+    if random {
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+    response
 }
-fn get_random_wonder_docs(op: TransformOperation) -> TransformOperation {
+fn get_random_wonder_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
     op.summary("Specific wonder - random")
         .description(
-            "Get a random wonder, after filtering wonders based on provided query parameters",
+            "Get a random wonder, after filtering wonders based on provided query parameters. If \
+            `count` is provided, a list of that many distinct wonders is returned instead. \
+            Providing a `seed` makes the selection reproducible. Wonders can be made more likely \
+            to be selected based on their popularity, category or time period. Wonders can be \
+            returned without repeats using a shuffle session, started with `session=new` and \
+            continued by passing the token from the `X-Shuffle-Session` response header as the \
+            `session` parameter. Sessions can instead favour the wonders they haven't returned \
+            yet, using `favour_unseen`",
         )
-        .summary("Random wonder")
         .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[20]))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::NoWondersLeft))
+        .with(|op| {
+            errors_docs(
+                op,
+                version,
+                &[Error::NoWondersLeft, Error::NotEnoughWonders(10, 7)],
+            )
+        })
+}
+
+// GET WONDER OF THE DAY
+async fn get_daily_wonder(
+    Garde(Query(filtering_params)): Garde<Query<WonderParamsFiltering>>,
+    Garde(Query(daily_params)): Garde<Query<DailyParams>>,
+) -> impl IntoApiResponse {
+    let date = match requested_date(&daily_params, Utc::now()) {
+        Ok(date) => date,
+        Err(e) => return e.into_response(),
+    };
+    let wonders = match filter_wonders(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
+    };
+
+    wonder_response(daily_wonder(&wonders, date))
+}
+fn get_daily_wonder_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
+    op.summary("Specific wonder - of the day")
+        .description(
+            "Get the wonder of the day for the given date, after filtering wonders based on \
+            provided query parameters. The same wonder is returned for the same date and \
+            filters, and no wonder is repeated until all of the filtered wonders have been \
+            returned",
+        )
+        .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[5]))
+        .with(|op| {
+            error_docs(
+                op,
+                version,
+                Error::InvalidRequest("Invalid date '2024-13-01', expected YYYY-MM-DD".to_string()),
+            )
         })
 }
 
@@ -312,27 +1671,18 @@ async fn get_oldest_wonder(
 ) -> impl IntoApiResponse {
     assert!(!WONDERS.is_empty());
 
-    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-    if let Err(e) = filter_wonders(&mut wonders, filtering_params) {
-        return e.into_response();
+    let wonders = match filter_wonders(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
     };
 
-    Json(
-        wonders
-            .iter()
-            .reduce(|a, b| if a.build_year < b.build_year { a } else { b })
-            .unwrap(),
-    )
-    .into_response()
+    wonder_response(oldest_wonder(&wonders).unwrap())
 }
-fn get_oldest_wonder_docs(op: TransformOperation) -> TransformOperation {
+fn get_oldest_wonder_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
     op.summary("Specific wonder - oldest")
         .description("Get the oldest (least recently built) wonder, after filtering wonders based on provided query parameters")
         .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[2]))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::NoWondersLeft))
-        })
+        .with(|op| error_docs(op, version, Error::NoWondersLeft))
 }
 
 // GET YOUNGEST WONDER
@@ -341,27 +1691,18 @@ async fn get_youngest_wonder(
 ) -> impl IntoApiResponse {
     assert!(!WONDERS.is_empty());
 
-    let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-    if let Err(e) = filter_wonders(&mut wonders, filtering_params) {
-        return e.into_response();
+    let wonders = match filter_wonders(filtering_params) {
+        Ok(wonders) => wonders,
+        Err(e) => return e.into_response(),
     };
 
-    Json(
-        wonders
-            .iter()
-            .reduce(|a, b| if a.build_year > b.build_year { a } else { b })
-            .unwrap(),
-    )
-    .into_response()
+    wonder_response(youngest_wonder(&wonders).unwrap())
 }
-fn get_youngest_wonder_docs(op: TransformOperation) -> TransformOperation {
+fn get_youngest_wonder_docs(op: TransformOperation, version: ApiVersion) -> TransformOperation {
     op.summary("Specific wonder - youngest")
         .description("Get the youngest (most recently built) wonder, after filtering wonders based on provided query parameters")
         .response_with::<200, Json<&'static Wonder>, _>(|res| res.example(&WONDERS[10]))
-        .response_with::<400, ErrorResponse, _>(|res| {
-            res.description("Bad request")
-                .example(ErrorResponse::new(Error::NoWondersLeft))
-        })
+        .with(|op| error_docs(op, version, Error::NoWondersLeft))
 }
 
 #[cfg(test)]
@@ -376,106 +1717,104 @@ mod tests {
     // UNIT TESTS - HELPERS
     #[test]
     fn test_filter_wonders_ok() {
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                category: Some(Category::SevenWonders),
-                ..Default::default()
-            },
-        )
-        .is_ok());
+        let wonders = filter_wonders(WonderParamsFiltering {
+            category: Some(Category::SevenWonders),
+            ..Default::default()
+        })
+        .unwrap();
         assert_eq!(wonders.len(), 7);
 
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                name: Some("al".to_string()),
-                time_period: Some(TimePeriod::PostClassical),
-                ..Default::default()
-            },
-        )
-        .is_ok());
-        wonders.iter().for_each(|w| {
+        let wonders = filter_wonders(WonderParamsFiltering {
+            name: Some("al".to_string()),
+            time_period: Some(TimePeriod::PostClassical),
+            ..Default::default()
+        })
+        .unwrap();
+        wonders.iter().map(|&i| &WONDERS[i]).for_each(|w| {
             assert_eq!(w.time_period, TimePeriod::PostClassical);
             assert!(w.name.to_lowercase().contains("al"));
         });
 
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                location: Some("ro".to_string()),
-                lower_limit: Some(-200),
-                upper_limit: Some(1000),
-                ..Default::default()
-            },
-        )
-        .is_ok());
-        wonders.iter().for_each(|w| {
+        let wonders = filter_wonders(WonderParamsFiltering {
+            location: Some("ro".to_string()),
+            lower_limit: Some(-200),
+            upper_limit: Some(1000),
+            ..Default::default()
+        })
+        .unwrap();
+        wonders.iter().map(|&i| &WONDERS[i]).for_each(|w| {
             assert!(w.build_year >= -200);
             assert!(w.build_year <= 1000);
             assert!(w.location.to_lowercase().contains("ro"));
         });
+
+        // Case-insensitive
+        assert_eq!(
+            filter_wonders(WonderParamsFiltering {
+                location: Some("RO".to_string()),
+                lower_limit: Some(-200),
+                upper_limit: Some(1000),
+                ..Default::default()
+            })
+            .unwrap(),
+            wonders
+        );
+
+        // Category and time period
+        let wonders = filter_wonders(WonderParamsFiltering {
+            category: Some(Category::SevenWonders),
+            time_period: Some(TimePeriod::Classical),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            wonders,
+            (0..WONDERS.len())
+                .filter(|&i| WONDERS[i].categories.contains(&Category::SevenWonders)
+                    && WONDERS[i].time_period == TimePeriod::Classical)
+                .collect::<Vec<usize>>()
+        );
     }
 
     #[test]
     fn test_filter_wonders_errors() {
         // Empty
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                name: Some("abcdefghijk".to_string()),
-                ..Default::default()
-            },
-        )
+        assert!(filter_wonders(WonderParamsFiltering {
+            name: Some("abcdefghijk".to_string()),
+            ..Default::default()
+        })
         .is_err_and(|e| matches!(e, Error::NoWondersLeft)));
-        assert_eq!(wonders.len(), 0);
 
         // Conflicting limits
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                lower_limit: Some(500),
-                upper_limit: Some(400),
-                ..Default::default()
-            },
-        )
+        assert!(filter_wonders(WonderParamsFiltering {
+            lower_limit: Some(500),
+            upper_limit: Some(400),
+            ..Default::default()
+        })
         .is_err_and(|e| matches!(e, Error::ConflictingLimitParams(500, 400))));
     }
 
     #[test]
     fn test_filter_wonders_ignore_empty() {
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders_ignore_empty(
-            &mut wonders,
-            WonderParamsFiltering {
-                name: Some("abcdefghijk".to_string()),
-                ..Default::default()
-            },
-        )
-        .is_ok());
+        let wonders = filter_wonders_ignore_empty(WonderParamsFiltering {
+            name: Some("abcdefghijk".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
         assert_eq!(wonders.len(), 0);
 
         // Other errors should not be ignored
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
-        assert!(filter_wonders(
-            &mut wonders,
-            WonderParamsFiltering {
-                lower_limit: Some(1000),
-                upper_limit: Some(-400),
-                ..Default::default()
-            },
-        )
+        assert!(filter_wonders_ignore_empty(WonderParamsFiltering {
+            lower_limit: Some(1000),
+            upper_limit: Some(-400),
+            ..Default::default()
+        })
         .is_err_and(|e| matches!(e, Error::ConflictingLimitParams(1000, -400))));
     }
 
     #[test]
     fn test_sort_wonders() {
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
+        let mut wonders: Vec<usize> = (0..WONDERS.len()).collect();
 
         sort_wonders(
             &mut wonders,
@@ -484,13 +1823,23 @@ mod tests {
                 sort_reverse: None,
             },
         );
-        wonders.iter().reduce(|a, b| {
+        wonders.iter().map(|&i| &WONDERS[i]).reduce(|a, b| {
             assert!(a.build_year <= b.build_year);
             b
         });
 
-        // Reset working `Vec`, as sorting is stable
-        let mut wonders: Vec<&Wonder> = WONDERS.iter().collect();
+        // Sorting should be stable
+        let mut expected: Vec<&Wonder> = WONDERS.iter().collect();
+        expected.sort_by_key(|w| w.build_year);
+        assert_eq!(
+            wonders
+                .iter()
+                .map(|&i| &WONDERS[i])
+                .collect::<Vec<&Wonder>>(),
+            expected
+        );
+
+        let mut wonders: Vec<usize> = (0..WONDERS.len()).collect();
 
         sort_wonders(
             &mut wonders,
@@ -499,12 +1848,501 @@ mod tests {
                 sort_reverse: Some(true),
             },
         );
-        wonders.iter().reduce(|a, b| {
+        wonders.iter().map(|&i| &WONDERS[i]).reduce(|a, b| {
             assert!(a.name > b.name);
             b
         });
     }
 
+    #[test]
+    fn test_wonder_stats() {
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+        let stats = wonder_stats(&wonders, 1000);
+        assert_eq!(stats.count, WONDERS.len());
+        assert_eq!(stats.by_category[&Category::SevenWonders], 7);
+        assert_eq!(stats.by_time_period.values().sum::<usize>(), WONDERS.len());
+        assert_eq!(stats.by_continent.values().sum::<usize>(), WONDERS.len());
+
+        let build_year = stats.build_year.unwrap();
+        assert_eq!(
+            build_year.min,
+            WONDERS.iter().map(|w| w.build_year).min().unwrap()
+        );
+        assert_eq!(
+            build_year.max,
+            WONDERS.iter().map(|w| w.build_year).max().unwrap()
+        );
+
+        let histogram = &stats.build_year_histogram;
+        assert_eq!(
+            histogram.iter().map(|b| b.count).sum::<usize>(),
+            WONDERS.len()
+        );
+        assert!(histogram[0].start <= i32::from(build_year.min));
+        assert!(histogram.last().unwrap().end > i32::from(build_year.max));
+        histogram.iter().reduce(|a, b| {
+            assert_eq!(a.end, b.start);
+            assert_eq!(b.end - b.start, 1000);
+            b
+        });
+
+        // Empty categories are still included
+        let stats = wonder_stats(&[], 100);
+        assert_eq!(stats.by_category.len(), Category::iter().count());
+        assert!(stats.by_category.values().all(|&count| count == 0));
+        assert_eq!(stats.build_year, None);
+        assert!(stats.build_year_histogram.is_empty());
+    }
+
+    #[test]
+    fn test_build_year_stats() {
+        assert_eq!(
+            build_year_stats(&[-2560, -100, 80, 1900]),
+            Some(BuildYearStats {
+                min: -2560,
+                max: 1900,
+                median: -10.0,
+                mean: -170.0,
+            })
+        );
+        assert_eq!(build_year_stats(&[5, 7, 100]).unwrap().median, 7.0);
+        assert_eq!(build_year_stats(&[]), None);
+    }
+
+    #[test]
+    fn test_group_wonders() {
+        let mut wonders: Vec<usize> = (0..WONDERS.len()).collect();
+        sort_wonders(
+            &mut wonders,
+            WonderParamsSorting {
+                sort_by: Some(SortBy::Alphabetical),
+                sort_reverse: None,
+            },
+        );
+
+        for group_by in GroupBy::iter() {
+            let groups = group_wonders(&wonders, group_by);
+            assert!(groups.iter().all(|(_, group)| !group.is_empty()));
+            // Sorting is kept within groups
+            for (_, group) in &groups {
+                group.iter().map(|&i| &WONDERS[i]).reduce(|a, b| {
+                    assert!(a.name < b.name);
+                    b
+                });
+            }
+            if group_by != GroupBy::Category {
+                assert_eq!(
+                    groups.iter().map(|(_, group)| group.len()).sum::<usize>(),
+                    WONDERS.len()
+                );
+            }
+        }
+
+        let groups = group_wonders(&wonders, GroupBy::TimePeriod);
+        let keys: Vec<&str> = groups.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "Prehistoric",
+                "Ancient",
+                "Classical",
+                "PostClassical",
+                "EarlyModern",
+                "Modern"
+            ]
+        );
+
+        let groups = group_wonders(&wonders, GroupBy::Century);
+        groups.iter().for_each(|(key, group)| {
+            for &i in group {
+                assert_eq!(
+                    period(WONDERS[i].build_year, Resolution::Century).label,
+                    *key
+                );
+            }
+        });
+        assert!(groups[0].0.ends_with("BCE"));
+        assert!(!groups.last().unwrap().0.ends_with("BCE"));
+    }
+
+    #[test]
+    fn test_period() {
+        for (year, resolution, expected) in [
+            (
+                -2560,
+                Resolution::Century,
+                (-2600, -2501, "26th century BCE"),
+            ),
+            (-101, Resolution::Century, (-200, -101, "2nd century BCE")),
+            (-100, Resolution::Century, (-100, -1, "1st century BCE")),
+            (0, Resolution::Century, (-100, -1, "1st century BCE")),
+            (1, Resolution::Century, (1, 100, "1st century CE")),
+            (101, Resolution::Century, (101, 200, "2nd century CE")),
+            (1112, Resolution::Century, (1101, 1200, "12th century CE")),
+            (1900, Resolution::Century, (1801, 1900, "19th century CE")),
+            (2001, Resolution::Century, (2001, 2100, "21st century CE")),
+            (
+                -2560,
+                Resolution::Millennium,
+                (-3000, -2001, "3rd millennium BCE"),
+            ),
+            (1000, Resolution::Millennium, (1, 1000, "1st millennium CE")),
+            (1929, Resolution::Decade, (1920, 1929, "1920s CE")),
+            (5, Resolution::Decade, (1, 9, "0s CE")),
+            (-2560, Resolution::Decade, (-2569, -2560, "2560s BCE")),
+        ] {
+            let (start, end, label) = expected;
+            assert_eq!(
+                period(year, resolution),
+                Period {
+                    start,
+                    end,
+                    label: label.to_string()
+                },
+                "{year} {resolution:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeline() {
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+        for resolution in Resolution::iter() {
+            let buckets = timeline(&wonders, resolution);
+            assert_eq!(
+                buckets.iter().map(|b| b.wonders.len()).sum::<usize>(),
+                WONDERS.len()
+            );
+            buckets.iter().reduce(|a, b| {
+                assert!(a.end < b.start);
+                b
+            });
+            for bucket in &buckets {
+                assert!(!bucket.wonders.is_empty());
+                assert!(bucket
+                    .wonders
+                    .windows(2)
+                    .all(|w| WONDERS[w[0]].build_year <= WONDERS[w[1]].build_year));
+                for &i in &bucket.wonders {
+                    assert_eq!(
+                        period(WONDERS[i].build_year, resolution).label,
+                        bucket.label
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_years_between() {
+        assert_eq!(years_between(1900, 1850), 50);
+        assert_eq!(years_between(-2560, -100), 2460);
+        // No year 0
+        assert_eq!(years_between(-1, 1), 1);
+        assert_eq!(years_between(-100, 80), 179);
+        // Year 0 is 1 BCE, as for periods
+        assert_eq!(years_between(0, -1), 0);
+        assert_eq!(years_between(0, 1), 1);
+        assert_eq!(years_between(0, -100), 99);
+        assert_eq!(years_between(i16::MIN, i16::MAX), 65534);
+    }
+
+    #[test]
+    fn test_compare_wonders() {
+        let find = |name: &str| find_wonder_by_name(name).unwrap();
+
+        let comparison = compare_wonders(find("colosseum"), find("colosseum"));
+        assert_eq!(comparison.age_difference_years, 0);
+        assert!(comparison.built_close_together);
+        assert_eq!(comparison.distance_km, Some(0.0));
+        assert_eq!(
+            comparison.shared_categories,
+            WONDERS[find("colosseum")].categories
+        );
+        assert!(comparison.same_time_period);
+
+        let comparison = compare_wonders(find("great-pyramid-of-giza"), find("stonehenge"));
+        assert!(!comparison.built_close_together);
+        assert!(comparison
+            .distance_km
+            .is_some_and(|d| d > 3000.0 && d < 4000.0));
+        assert_eq!(comparison.a, find("great-pyramid-of-giza"));
+    }
+
+    #[test]
+    fn test_related_wonders() {
+        let colosseum = find_wonder_by_name("colosseum").unwrap();
+
+        let related = related_wonders(colosseum, WONDERS.len());
+        assert_eq!(related.len(), WONDERS.len() - 1);
+        assert!(related.iter().all(|r| r.wonder != colosseum));
+        related.iter().reduce(|a, b| {
+            assert!(a.score >= b.score);
+            b
+        });
+        for related in &related {
+            let breakdown = &related.breakdown;
+            for score in [
+                breakdown.categories,
+                breakdown.time_period,
+                breakdown.distance,
+                breakdown.summary,
+            ] {
+                assert!((0.0..=1.0).contains(&score), "{related:?}");
+            }
+            assert!((related.score - breakdown.average()).abs() < 0.001);
+        }
+
+        assert_eq!(related_wonders(colosseum, 3).len(), 3);
+        assert_eq!(related_wonders(colosseum, 3)[0].wonder, related[0].wonder);
+    }
+
+    #[tokio::test]
+    async fn test_wonders_response() {
+        for wonders in [vec![3, 0, 5], vec![1], vec![]] {
+            let response = wonders_response(&wonders);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let expected: Vec<&Wonder> = wonders.iter().map(|&i| &WONDERS[i]).collect();
+            assert_eq!(body, serde_json::to_vec(&expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_random_wonders() {
+        let uniform = RandomWeights::default();
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+
+        let selected = random_wonders(&wonders, 10, None, &uniform).unwrap();
+        assert_eq!(selected.len(), 10);
+        let mut distinct = selected.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 10);
+
+        // Reproducible using the same seed
+        let selected = random_wonders(&wonders, 5, Some(7), &uniform).unwrap();
+        assert_eq!(
+            selected,
+            random_wonders(&wonders, 5, Some(7), &uniform).unwrap()
+        );
+        assert_ne!(
+            selected,
+            random_wonders(&wonders, 5, Some(8), &uniform).unwrap()
+        );
+
+        assert_eq!(
+            random_wonders(&[4, 2], 2, Some(1), &uniform).unwrap().len(),
+            2
+        );
+        assert!(matches!(
+            random_wonders(&[4, 2], 3, None, &uniform),
+            Err(Error::NotEnoughWonders(3, 2))
+        ));
+    }
+
+    #[test]
+    fn test_random_weights() {
+        let find = |name: &str| find_wonder_by_name(name).unwrap();
+        let (pyramid, prora) = (find("great-pyramid-of-giza"), find("prora"));
+
+        assert_eq!(RandomWeights::default().weight(pyramid), 1.0);
+        let weights = RandomWeights {
+            popularity: true,
+            category: Some(Category::SevenWonders),
+            time_period: Some(TimePeriod::Ancient),
+            ..Default::default()
+        };
+        assert_eq!(weights.weight(pyramid), 10.0 * 5.0 * 5.0);
+        assert_eq!(weights.weight(prora), 1.0);
+
+        // The more popular wonder comes first around 10 times as often
+        let weights = RandomWeights {
+            popularity: true,
+            ..Default::default()
+        };
+        let pyramid_first = (0..1000)
+            .filter(|&seed| {
+                random_wonders(&[prora, pyramid], 1, Some(seed), &weights).unwrap()[0] == pyramid
+            })
+            .count();
+        assert!((850..=970).contains(&pyramid_first), "{pyramid_first}");
+
+        // Sessions still take each wonder once per cycle
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+        let mut session = ShuffleSession::new(3);
+        let mut taken = session.take(&wonders, wonders.len(), &weights).unwrap();
+        taken.sort_unstable();
+        assert_eq!(taken, wonders);
+    }
+
+    #[test]
+    fn test_random_weights_unseen() {
+        let weights = RandomWeights {
+            unseen: true,
+            ..Default::default()
+        };
+        let wonders = [1, 3, 5, 7];
+
+        // Repeats are possible, but unseen wonders are favoured
+        let mut session = ShuffleSession::new(5);
+        let (mut repeats, mut seen) = (0, Vec::new());
+        for i in 0..4000 {
+            if i % wonders.len() == 0 {
+                seen.clear();
+            }
+            let wonder = session.take(&wonders, 1, &weights).unwrap()[0];
+            if seen.contains(&wonder) {
+                repeats += 1;
+            }
+            seen.push(wonder);
+        }
+        assert!(repeats > 0);
+        // Without favouring, 3/8 of draws would repeat a wonder from earlier in the cycle
+        assert!(repeats < 4000 * 3 / 8 / 2, "{repeats}");
+
+        // Wonders seen earlier in the cycle are found again when continuing a session
+        let mut session = ShuffleSession::new(5);
+        let mut seen = Vec::new();
+        for index in 0..10 {
+            if index % 4 == 0 {
+                seen.clear();
+            }
+            let wonder = unseen_favouring_draw(&wonders, 5, index, &weights, &seen);
+            seen.push(wonder);
+            assert_eq!(session.take(&wonders, 1, &weights).unwrap(), [wonder]);
+        }
+    }
+
+    #[test]
+    fn test_shuffled_sequence() {
+        let uniform = RandomWeights::default();
+        let wonders = [1, 3, 5, 7];
+        let sequence: Vec<usize> = (0..40)
+            .map(|i| shuffled_sequence(&wonders, 9, i, &uniform))
+            .collect();
+        assert!(sequence.windows(2).all(|pair| pair[0] != pair[1]));
+        for cycle in sequence.chunks_exact(wonders.len()) {
+            let mut cycle = cycle.to_vec();
+            cycle.sort_unstable();
+            assert_eq!(cycle, wonders);
+        }
+        assert_ne!(
+            sequence,
+            (0..40)
+                .map(|i| shuffled_sequence(&wonders, 10, i, &uniform))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_shuffle_session() {
+        let uniform = RandomWeights::default();
+        let wonders: Vec<usize> = (0..WONDERS.len()).collect();
+
+        let mut session = ShuffleSession::new(u64::MAX);
+        let token = session.to_string();
+        assert_eq!(token.parse::<ShuffleSession>().unwrap(), session);
+        let mut seen = Vec::new();
+        for _ in 0..wonders.len() {
+            seen.extend(session.take(&wonders, 1, &uniform).unwrap());
+        }
+        seen.sort_unstable();
+        assert_eq!(seen, wonders);
+
+        // Distinct across the end of a cycle
+        let taken = session.take(&wonders[..5], 5, &uniform).unwrap();
+        let mut distinct = taken.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 5);
+        assert!(session.take(&wonders[..5], 6, &uniform).is_err());
+
+        for token in ["", "abc", "1-", "-1", "1--1", "g-1", "1-8000000000000000"] {
+            assert!(token.parse::<ShuffleSession>().is_err(), "{token}");
+        }
+    }
+
+    #[test]
+    fn test_daily_wonder() {
+        let wonders = filter_wonders(WonderParamsFiltering {
+            category: Some(Category::SevenWonders),
+            ..Default::default()
+        })
+        .unwrap();
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let selected: Vec<usize> = start
+            .iter_days()
+            .take(wonders.len() * 10)
+            .map(|date| daily_wonder(&wonders, date))
+            .collect();
+
+        // Deterministic
+        assert_eq!(selected[0], daily_wonder(&wonders, start));
+        // No repeats on consecutive days, including between cycles
+        assert!(selected.windows(2).all(|days| days[0] != days[1]));
+        // Every wonder is selected once per cycle
+        let first_day = (start - NaiveDate::default()).num_days();
+        let cycle_start = wonders.len() - first_day.rem_euclid(wonders.len() as i64) as usize;
+        for cycle in selected[cycle_start..].chunks_exact(wonders.len()) {
+            let mut cycle = cycle.to_vec();
+            cycle.sort_unstable();
+            assert_eq!(cycle, wonders);
+        }
+
+        // Dates before 1970 are also supported
+        let old_date = NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+        assert!(wonders.contains(&daily_wonder(&wonders, old_date)));
+        assert_eq!(daily_wonder(&[3], start), 3);
+    }
+
+    #[test]
+    fn test_requested_date() {
+        let params = |date: Option<&str>, tz: Option<&str>| DailyParams {
+            date: date.map(str::to_string),
+            tz: tz.map(str::to_string),
+        };
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let now = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            requested_date(&params(Some("2024-02-29"), Some("+05:00")), Utc::now()).unwrap(),
+            day(2024, 2, 29)
+        );
+        assert!(requested_date(&params(Some("2023-02-29"), None), Utc::now()).is_err());
+        assert!(requested_date(&params(Some("29/02/2024"), None), Utc::now()).is_err());
+
+        let late = now("2024-07-01T22:30:00Z");
+        for (tz, expected) in [
+            (None, day(2024, 7, 1)),
+            (Some("UTC"), day(2024, 7, 1)),
+            (Some("+01:00"), day(2024, 7, 1)),
+            (Some("+05:30"), day(2024, 7, 2)),
+            (Some("-12:00"), day(2024, 7, 1)),
+            // Summer time, at UTC+2
+            (Some("Europe/Oslo"), day(2024, 7, 2)),
+            (Some("America/Los_Angeles"), day(2024, 7, 1)),
+        ] {
+            assert_eq!(requested_date(&params(None, tz), late).unwrap(), expected);
+        }
+        // Standard time, at UTC+1
+        assert_eq!(
+            requested_date(
+                &params(None, Some("Europe/Oslo")),
+                now("2024-01-01T22:30:00Z")
+            )
+            .unwrap(),
+            day(2024, 1, 1)
+        );
+
+        for tz in ["Europe/Atlantis", "+25:00", " 05:30"] {
+            assert!(requested_date(&params(None, Some(tz)), late).is_err());
+        }
+    }
+
     // UNIT TESTS - ROUTES
     #[tokio::test]
     async fn test_get_all_wonders() {
@@ -515,6 +2353,19 @@ mod tests {
 
         let wonders = extract_response!(server, Vec<Wonder>, "/?category=SevenWonders");
         assert_eq!(wonders.len(), 7);
+
+        let groups = extract_response!(
+            server,
+            serde_json::Map<String, serde_json::Value>,
+            "/?category=SevenWonders&group_by=continent"
+        );
+        assert_eq!(
+            groups
+                .values()
+                .map(|group| group.as_array().unwrap().len())
+                .sum::<usize>(),
+            7
+        );
     }
 
     #[tokio::test]
@@ -526,6 +2377,60 @@ mod tests {
 
         let count = extract_response!(server, u16, "/?category=SevenWonders");
         assert_eq!(count as usize, 7);
+
+        let counts = extract_response!(
+            server,
+            serde_json::Map<String, serde_json::Value>,
+            "/?group_by=time_period"
+        );
+        assert_eq!(
+            counts["Modern"],
+            WONDERS
+                .iter()
+                .filter(|w| w.time_period == TimePeriod::Modern)
+                .count()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_wonder_stats() {
+        let server = get_route_server!(get_wonder_stats);
+
+        let stats = extract_response!(server, WonderStats);
+        assert_eq!(stats.count, WONDERS.len());
+
+        let stats = extract_response!(
+            server,
+            WonderStats,
+            "/?category=SevenWonders&bucket_width=100"
+        );
+        assert_eq!(stats.count, 7);
+        assert_eq!(stats.by_category[&Category::SevenWonders], 7);
+        assert_eq!(
+            stats.build_year_histogram[0].end - stats.build_year_histogram[0].start,
+            100
+        );
+
+        server
+            .get("/?bucket_width=0")
+            .await
+            .assert_status(axum_valid::VALIDATION_ERROR_STATUS);
+    }
+
+    #[tokio::test]
+    async fn test_get_wonder_comparison() {
+        let server = get_route_server!(get_wonder_comparison);
+
+        let comparison =
+            extract_response!(server, WonderComparison<Wonder>, "/?a=alhambra&b=colosseum");
+        assert_eq!(comparison.a.name, "Alhambra");
+        assert_eq!(comparison.b.name, "Colosseum");
+
+        server
+            .get("/?a=alhambra&b=not-a-wonder")
+            .await
+            .assert_status_bad_request();
+        server.get("/?a=alhambra").await.assert_status_bad_request();
     }
 
     #[tokio::test]
@@ -574,6 +2479,32 @@ mod tests {
         error_response.assert_status_bad_request();
     }
 
+    #[tokio::test]
+    async fn test_get_related_wonders() {
+        let app = Router::new().route("/:name", get(get_related_wonders));
+        let server = TestServer::new(app).unwrap();
+
+        let related = extract_response!(server, Vec<RelatedWonder<Wonder>>, "/colosseum");
+        assert_eq!(related.len(), DEFAULT_RELATED);
+        assert!(related.iter().all(|r| r.wonder.name != "Colosseum"));
+
+        let related = extract_response!(server, Vec<RelatedWonder<Wonder>>, "/colosseum?limit=2");
+        assert_eq!(related.len(), 2);
+
+        server
+            .get("/colosseum?limit=0")
+            .await
+            .assert_status_bad_request();
+        server
+            .get("/colosseum?limit=21")
+            .await
+            .assert_status_bad_request();
+        server
+            .get("/not-a-wonder")
+            .await
+            .assert_status_bad_request();
+    }
+
     #[tokio::test]
     async fn test_get_random_wonder() {
         let server = get_route_server!(get_random_wonder);
@@ -581,6 +2512,85 @@ mod tests {
         let response = server.get("/").await;
         let wonder = response.json::<Wonder>();
         assert!(WONDERS.contains(&wonder));
+
+        let wonders = extract_response!(server, Vec<Wonder>, "/?count=3&seed=1");
+        assert_eq!(wonders.len(), 3);
+        assert_eq!(
+            wonders,
+            extract_response!(server, Vec<Wonder>, "/?count=3&seed=1")
+        );
+        assert_eq!(
+            extract_response!(server, Wonder, "/?seed=1"),
+            extract_response!(server, Wonder, "/?seed=1")
+        );
+
+        let response = server.get("/?count=8&category=SevenWonders").await;
+        response.assert_status_bad_request();
+        assert_eq!(
+            response.json::<serde_json::Value>()["message"],
+            "Requested 8 wonders, but only 7 match the given filters"
+        );
+        server.get("/?count=0").await.assert_status_bad_request();
+
+        let wonders = extract_response!(
+            server,
+            Vec<Wonder>,
+            "/?count=3&seed=2&weight_by_popularity=true&favour_category=Civ6"
+        );
+        assert_eq!(wonders.len(), 3);
+
+        // Shuffle sessions
+        let mut token = "new".to_string();
+        let mut names = std::collections::HashSet::new();
+        for _ in 0..7 {
+            let response = server
+                .get(&format!("/?session={token}&category=SevenWonders"))
+                .await;
+            token = response
+                .header(&SHUFFLE_SESSION_HEADER)
+                .to_str()
+                .unwrap()
+                .to_string();
+            names.insert(response.json::<Wonder>().name);
+        }
+        assert_eq!(names.len(), 7);
+        server
+            .get("/?session=not-a-session")
+            .await
+            .assert_status_bad_request();
+        server
+            .get("/?session=new&favour_unseen=true")
+            .await
+            .assert_status_ok();
+        server
+            .get("/?favour_unseen=true")
+            .await
+            .assert_status_bad_request();
+    }
+
+    #[tokio::test]
+    async fn test_get_daily_wonder() {
+        let server = get_route_server!(get_daily_wonder);
+
+        let wonder = extract_response!(server, Wonder, "/?date=2024-06-01&category=SevenWonders");
+        assert_eq!(
+            wonder,
+            extract_response!(server, Wonder, "/?date=2024-06-01&category=SevenWonders")
+        );
+        assert!(wonder.categories.contains(&Category::SevenWonders));
+        for tz in ["-08:00", "%2B05:30", "Europe/Oslo"] {
+            let path = format!("/?tz={tz}");
+            assert!(WONDERS.contains(&extract_response!(server, Wonder, &path)));
+        }
+
+        server
+            .get("/?date=2024-06-31")
+            .await
+            .assert_status_bad_request();
+        server
+            .get("/?tz=Europe/Atlantis")
+            .await
+            .assert_status_bad_request();
     }
 
     #[tokio::test]
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+use crate::{
+    config::{CorsConfig, SecurityHeadersConfig},
+    request_id::REQUEST_ID_HEADER,
+    SHUFFLE_SESSION_HEADER,
+};
+
+/// Configuration value allowing any origin, method or header
+const WILDCARD: &str = "*";
+
+/// CORS layer for the given configuration. Rate limit, request ID and shuffle session headers are
+/// always exposed, so browser clients can back off, report failing requests and continue sessions
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let allow_origin = if config.allowed_origins.iter().any(|o| o == WILDCARD) {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.allowed_origins.iter().map(|origin| {
+            HeaderValue::from_str(origin)
+                .expect("Invalid `cors.allowed_origins` configuration value")
+        }))
+    };
+
+    let allow_methods = if config.allowed_methods.iter().any(|m| m == WILDCARD) {
+        AllowMethods::any()
+    } else {
+        AllowMethods::list(config.allowed_methods.iter().map(|method| {
+            Method::from_bytes(method.as_bytes())
+                .expect("Invalid `cors.allowed_methods` configuration value")
+        }))
+    };
+
+    let allow_headers = if config.allowed_headers.iter().any(|h| h == WILDCARD) {
+        AllowHeaders::any()
+    } else {
+        AllowHeaders::list(config.allowed_headers.iter().map(|name| {
+            HeaderName::from_bytes(name.as_bytes())
+                .expect("Invalid `cors.allowed_headers` configuration value")
+        }))
+    };
+
+    let cors = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(allow_methods)
+        .allow_headers(allow_headers)
+        .expose_headers([
+            HeaderName::from_static("ratelimit-limit"),
+            HeaderName::from_static("ratelimit-remaining"),
+            HeaderName::from_static("ratelimit-reset"),
+            HeaderName::from_static("x-quota-limit"),
+            HeaderName::from_static("x-quota-remaining"),
+            HeaderName::from_static("x-quota-reset"),
+            header::RETRY_AFTER,
+            REQUEST_ID_HEADER.clone(),
+            SHUFFLE_SESSION_HEADER.clone(),
+        ]);
+
+    match config.max_age {
+        Some(max_age) => cors.max_age(Duration::from_secs(max_age)),
+        None => cors,
+    }
+}
+
+// MIDDLEWARE -------------------------------------------------------------------------------------
+/// Security headers added to all responses, used as the state for [`security_headers`]
+#[derive(Clone)]
+pub struct SecurityHeaders(HeaderMap);
+impl SecurityHeaders {
+    pub fn new(config: &SecurityHeadersConfig) -> Self {
+        let mut headers = HeaderMap::new();
+
+        if let Some(hsts) = &config.hsts {
+            headers.insert(
+                header::STRICT_TRANSPORT_SECURITY,
+                HeaderValue::from_str(hsts)
+                    .expect("Invalid `security_headers.hsts` configuration value"),
+            );
+        }
+        if config.content_type_options {
+            headers.insert(
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            );
+        }
+        if let Some(referrer_policy) = &config.referrer_policy {
+            headers.insert(
+                header::REFERRER_POLICY,
+                HeaderValue::from_str(referrer_policy)
+                    .expect("Invalid `security_headers.referrer_policy` configuration value"),
+            );
+        }
+        if let Some(csp) = &config.content_security_policy {
+            headers.insert(
+                header::CONTENT_SECURITY_POLICY,
+                HeaderValue::from_str(csp).expect(
+                    "Invalid `security_headers.content_security_policy` configuration value",
+                ),
+            );
+        }
+
+        Self(headers)
+    }
+}
+
+/// Middleware adding security headers to all responses, without overriding any set by handlers
+pub async fn security_headers(
+    State(SecurityHeaders(headers)): State<SecurityHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    for (name, value) in &headers {
+        response
+            .headers_mut()
+            .entry(name)
+            .or_insert_with(|| value.clone());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_security_headers() {
+        let SecurityHeaders(headers) = SecurityHeaders::new(&SecurityHeadersConfig {
+            hsts: Some("max-age=31536000".to_string()),
+            content_type_options: true,
+            referrer_policy: Some("no-referrer".to_string()),
+            content_security_policy: Some("default-src 'self'".to_string()),
+        });
+        assert_eq!(headers["strict-transport-security"], "max-age=31536000");
+        assert_eq!(headers["x-content-type-options"], "nosniff");
+        assert_eq!(headers["referrer-policy"], "no-referrer");
+        assert_eq!(headers["content-security-policy"], "default-src 'self'");
+
+        let SecurityHeaders(headers) = SecurityHeaders::new(&SecurityHeadersConfig {
+            hsts: None,
+            content_type_options: false,
+            referrer_policy: None,
+            content_security_policy: None,
+        });
+        assert!(headers.is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    future::IntoFuture,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use axum::{extract::ConnectInfo, Extension, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use tokio::task::JoinSet;
+
+use crate::{config::NetworkConfig, shutdown_signal, tls, DOCS_ROUTE};
+
+/// Time to wait before accepting connections again after a failure, e.g. when out of file
+/// descriptors
+pub(crate) const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A socket the API can be served on
+pub enum Listener {
+    Tcp(std::net::TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+/// Bind all listeners given in the configuration, or take those passed by systemd socket
+/// activation instead if there are any
+pub fn bind(config: &NetworkConfig) -> io::Result<Vec<Listener>> {
+    #[cfg(unix)]
+    if let Some(listeners) = systemd::listeners()? {
+        tracing::debug!("Using {} socket(s) passed by systemd", listeners.len());
+        return Ok(listeners);
+    }
+
+    let mut listeners = vec![Listener::Tcp(bind_tcp(config.host_addr(config.port))?)];
+    for addr in &config.binds {
+        listeners.push(Listener::Tcp(bind_tcp(addr.as_str())?));
+    }
+
+    if let Some(unix_socket) = &config.unix_socket {
+        #[cfg(unix)]
+        listeners.push(Listener::Unix(bind_unix(unix_socket)?));
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Unix sockets are not supported on this platform: {}",
+                unix_socket.path
+            ),
+        ));
+    }
+
+    Ok(listeners)
+}
+
+fn bind_tcp(
+    addr: impl std::net::ToSocketAddrs + std::fmt::Debug,
+) -> io::Result<std::net::TcpListener> {
+    std::net::TcpListener::bind(&addr)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed binding {addr:?}: {e}")))
+}
+
+#[cfg(unix)]
+fn bind_unix(
+    config: &crate::config::UnixSocketConfig,
+) -> io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    // Remove a socket left behind by a previous run, but never any other kind of file
+    if let Ok(metadata) = std::fs::symlink_metadata(&config.path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(&config.path)?;
+        }
+    }
+
+    let listener = std::os::unix::net::UnixListener::bind(&config.path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed binding {}: {e}", config.path)))?;
+    if let Some(mode) = config.mode {
+        std::fs::set_permissions(&config.path, std::fs::Permissions::from_mode(mode))?;
+    }
+
+    Ok(listener)
+}
+
+/// Serve the app on the listeners given in the configuration until shut down, using HTTPS if a TLS
+/// configuration loaded from `config.tls` is provided
+pub async fn serve(
+    app: Router,
+    config: &NetworkConfig,
+    tls: Option<RustlsConfig>,
+) -> io::Result<()> {
+    let listeners = bind(config)?;
+
+    let Some(rustls_config) = tls else {
+        return serve_listeners(app, listeners, None).await;
+    };
+
+    if let Some(port) = config.tls.as_ref().and_then(|tls| tls.redirect_http_port) {
+        let listener = tokio::net::TcpListener::from_std(bind_tcp(config.host_addr(port))?)?;
+        tracing::debug!(
+            "Redirecting http://{} to HTTPS",
+            listener.local_addr().unwrap()
+        );
+
+        let redirect = axum::serve(listener, tls::redirect_router(config.port))
+            .with_graceful_shutdown(shutdown_signal());
+        tokio::spawn(async move {
+            if let Err(e) = redirect.await {
+                tracing::error!("HTTP redirect server failed: {e}");
+            }
+        });
+    }
+
+    serve_listeners(app, listeners, Some(rustls_config)).await
+}
+
+/// Serve the app on the given listeners until shut down, using TLS for TCP listeners if a TLS
+/// configuration is provided
+pub async fn serve_listeners(
+    app: Router,
+    listeners: Vec<Listener>,
+    tls: Option<RustlsConfig>,
+) -> io::Result<()> {
+    let mut servers = JoinSet::new();
+    // `axum_server` uses a handle for graceful shutdown
+    let handle = axum_server::Handle::new();
+
+    for listener in listeners {
+        match listener {
+            Listener::Tcp(listener) => {
+                listener.set_nonblocking(true)?;
+                let addr = listener.local_addr()?;
+                let make_service = app
+                    .clone()
+                    .into_make_service_with_connect_info::<SocketAddr>();
+
+                match &tls {
+                    Some(rustls_config) => {
+                        tracing::debug!("Listening at https://{addr}");
+                        tracing::debug!("Example docs available at https://{addr}{DOCS_ROUTE}");
+                        servers.spawn(
+                            axum_server::from_tcp_rustls(listener, rustls_config.clone())
+                                .handle(handle.clone())
+                                .serve(make_service),
+                        );
+                    }
+                    None => {
+                        tracing::debug!("Listening at http://{addr}");
+                        tracing::debug!("Example docs available at http://{addr}{DOCS_ROUTE}");
+                        let listener = tokio::net::TcpListener::from_std(listener)?;
+                        servers.spawn(
+                            axum::serve(listener, make_service)
+                                .with_graceful_shutdown(shutdown_signal())
+                                .into_future(),
+                        );
+                    }
+                }
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                listener.set_nonblocking(true)?;
+                tracing::debug!("Listening at {:?}", listener.local_addr()?);
+                let listener = tokio::net::UnixListener::from_std(listener)?;
+                servers.spawn(serve_unix(listener, app.clone()));
+            }
+        }
+    }
+
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        handle.graceful_shutdown(None);
+    });
+
+    while let Some(result) = servers.join_next().await {
+        result.expect("Server task panicked")?;
+    }
+
+    Ok(())
+}
+
+/// Serve the app on a Unix socket until shut down. Clients are treated as connecting from
+/// `127.0.0.1`, so they share a single rate limit unless the original client's address is
+/// forwarded in headers
+#[cfg(unix)]
+async fn serve_unix(listener: tokio::net::UnixListener, app: Router) -> io::Result<()> {
+    use hyper_util::{
+        rt::{TokioExecutor, TokioIo},
+        server::{conn::auto::Builder, graceful::GracefulShutdown},
+        service::TowerToHyperService,
+    };
+
+    let app = app.layer(Extension(ConnectInfo(SocketAddr::from((
+        Ipv4Addr::LOCALHOST,
+        0,
+    )))));
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    let mut shutdown = std::pin::pin!(shutdown_signal());
+
+    loop {
+        let stream = tokio::select! {
+            result = listener.accept() => match result {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::error!("Failed to accept Unix socket connection: {e}");
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let connection = builder
+            .serve_connection_with_upgrades(
+                TokioIo::new(stream),
+                TowerToHyperService::new(app.clone()),
+            )
+            .into_owned();
+        let connection = graceful.watch(connection);
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::debug!("Unix socket connection error: {e}");
+            }
+        });
+    }
+
+    graceful.shutdown().await;
+    Ok(())
+}
+
+// SYSTEMD ----------------------------------------------------------------------------------------
+/// Systemd socket activation, as described in
+/// https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html
+#[cfg(unix)]
+mod systemd {
+    use std::{
+        io,
+        os::fd::{FromRawFd, RawFd},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use super::Listener;
+
+    /// First file descriptor passed by systemd
+    const LISTEN_FDS_START: RawFd = 3;
+
+    /// Whether the sockets passed by systemd have been taken, which can only happen once
+    static TAKEN: AtomicBool = AtomicBool::new(false);
+
+    /// Take the sockets passed by systemd, if any were passed to this process
+    pub fn listeners() -> io::Result<Option<Vec<Listener>>> {
+        let Ok(pid) = std::env::var("LISTEN_PID") else {
+            return Ok(None);
+        };
+        if pid.parse() != Ok(std::process::id()) {
+            return Ok(None);
+        }
+        let count: RawFd = std::env::var("LISTEN_FDS")
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(0);
+        if count == 0 {
+            return Ok(None);
+        }
+
+        // The environment is left as is, as modifying it isn't safe once other threads are running.
+        // Child processes ignore it anyway, as `LISTEN_PID` won't match their PID
+        if TAKEN.swap(true, Ordering::SeqCst) {
+            return Err(io::Error::other(
+                "Sockets passed by systemd have already been taken",
+            ));
+        }
+
+        (LISTEN_FDS_START..LISTEN_FDS_START + count)
+            // SAFETY: systemd passes ownership of these file descriptors to this process, and they
+            // are only taken once as guarded by `TAKEN` above
+            .map(|fd| unsafe { from_fd(fd) })
+            .collect::<io::Result<_>>()
+            .map(Some)
+    }
+
+    /// Create a listener from a listening socket's file descriptor
+    ///
+    /// # Safety
+    ///
+    /// The file descriptor must be an open socket owned by the caller
+    pub(super) unsafe fn from_fd(fd: RawFd) -> io::Result<Listener> {
+        let socket = socket2::Socket::from_raw_fd(fd);
+        if socket.local_addr()?.as_socket().is_some() {
+            Ok(Listener::Tcp(socket.into()))
+        } else {
+            Ok(Listener::Unix(socket.into()))
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::{fd::IntoRawFd, unix::fs::PermissionsExt};
+
+    use super::*;
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_bind() {
+        let socket_path =
+            std::env::temp_dir().join(format!("world-wonders-api-{}.sock", std::process::id()));
+        let config = NetworkConfig {
+            host: "localhost".to_string(),
+            port: 0,
+            binds: vec!["127.0.0.1:0".to_string()],
+            unix_socket: Some(crate::config::UnixSocketConfig {
+                path: socket_path.to_string_lossy().into_owned(),
+                mode: Some(0o600),
+            }),
+            tls: None,
+        };
+
+        let listeners = bind(&config).unwrap();
+        assert_eq!(listeners.len(), 3);
+        assert!(matches!(listeners[0], Listener::Tcp(_)));
+        assert!(matches!(listeners[1], Listener::Tcp(_)));
+        assert!(matches!(listeners[2], Listener::Unix(_)));
+
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Sockets left behind are replaced
+        drop(listeners);
+        assert!(bind(&config).is_ok());
+        std::fs::remove_file(socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_systemd_from_fd() {
+        let tcp = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = tcp.local_addr().unwrap();
+        let Listener::Tcp(tcp) = (unsafe { systemd::from_fd(tcp.into_raw_fd()) }).unwrap() else {
+            panic!("Expected a TCP listener");
+        };
+        assert_eq!(tcp.local_addr().unwrap(), addr);
+
+        let socket_path = std::env::temp_dir().join(format!(
+            "world-wonders-api-systemd-{}.sock",
+            std::process::id()
+        ));
+        let unix = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let listener = unsafe { systemd::from_fd(unix.into_raw_fd()) }.unwrap();
+        assert!(matches!(listener, Listener::Unix(_)));
+        std::fs::remove_file(socket_path).unwrap();
+    }
+}
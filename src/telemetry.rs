@@ -0,0 +1,101 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use opentelemetry::{
+    trace::{TraceError, TracerProvider as _},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::config::{LogFormat, LoggingConfig};
+
+/// Identifies this service in exported traces
+const SERVICE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Flushes any spans which haven't been exported yet when dropped
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            // The global subscriber is still installed, but spans are no longer exported
+            if let Err(e) = provider.shutdown() {
+                tracing::error!("Failed to export remaining traces: {e}");
+            }
+        }
+    }
+}
+
+/// Build a subscriber which logs in the configured format, and exports spans using OTLP if an
+/// endpoint is configured. The filter from the `RUST_LOG` environment variable takes priority
+/// over the configured level
+pub fn subscriber(
+    config: &LoggingConfig,
+) -> Result<(impl Subscriber + Send + Sync, Telemetry), TraceError> {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+
+    let fmt_layer = match config.format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+    };
+
+    let provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(tracer_provider)
+        .transpose()?;
+    let otel_layer = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otel_layer);
+
+    Ok((subscriber, Telemetry { provider }))
+}
+
+/// Provider exporting spans in batches to the given OTLP (gRPC) endpoint
+fn tracer_provider(endpoint: &str) -> Result<TracerProvider, TraceError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", SERVICE_NAME)]))
+        .build())
+}
+
+/// Install the subscriber built by [`subscriber`] as the global default. The returned value
+/// should be kept until shutdown, so that remaining spans are exported
+pub fn init(config: &LoggingConfig) -> Result<Telemetry, TraceError> {
+    let (subscriber, telemetry) = subscriber(config)?;
+    subscriber.init();
+    Ok(telemetry)
+}
+
+/// Record the matched path in the current request span. Needs to be applied to each nested router
+/// as well as the top-level router, as the matched path of nested routes is only known once the
+/// request has been routed to them
+pub async fn record_matched_path(req: Request, next: Next) -> Response {
+    if let Some(path) = req.extensions().get::<MatchedPath>() {
+        // Nested routes at `/` are reported with a trailing slash
+        let path = match path.as_str().trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+        tracing::Span::current().record("matched_path", path);
+    }
+
+    next.run(req).await
+}
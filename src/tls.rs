@@ -0,0 +1,173 @@
+use std::{io, time::Duration, time::SystemTime};
+
+use axum::{
+    extract::Request,
+    http::{header, uri::Authority},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+
+use crate::{config::TlsConfig, error::Error};
+
+/// How often the certificate and key files are checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Load the certificate and key given in the configuration
+pub async fn load(config: &TlsConfig) -> io::Result<RustlsConfig> {
+    RustlsConfig::from_pem_file(&config.cert_path, &config.key_path).await
+}
+
+/// Load the certificate and key given in the configuration, and reload them in the background
+/// whenever either file changes
+pub async fn load_and_watch(config: &TlsConfig) -> io::Result<RustlsConfig> {
+    let rustls_config = load(config).await?;
+    tokio::spawn(reload_on_change(rustls_config.clone(), config.clone()));
+    Ok(rustls_config)
+}
+
+/// Reload the certificate and key whenever either file changes, to be run as a background task
+pub async fn reload_on_change(rustls_config: RustlsConfig, config: TlsConfig) {
+    let mut modified = modified_times(&config);
+    let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+    loop {
+        interval.tick().await;
+        reload_if_changed(&rustls_config, &config, &mut modified).await;
+    }
+}
+
+/// Reload the certificate and key if either file has been modified since `modified`, returning
+/// whether they were reloaded
+async fn reload_if_changed(
+    rustls_config: &RustlsConfig,
+    config: &TlsConfig,
+    modified: &mut Option<(SystemTime, SystemTime)>,
+) -> bool {
+    let current = modified_times(config);
+    if current == *modified {
+        return false;
+    }
+
+    match rustls_config
+        .reload_from_pem_file(&config.cert_path, &config.key_path)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!("Reloaded TLS certificate from {}", config.cert_path);
+            *modified = current;
+            true
+        }
+        // Keep serving the current certificate - the files may only be partially written, so try
+        // again on the next check
+        Err(e) => {
+            tracing::error!("Failed to reload TLS certificate: {e}");
+            false
+        }
+    }
+}
+
+/// Modification times of the certificate and key files
+fn modified_times(config: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+    let modified = |path: &str| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    Some((modified(&config.cert_path)?, modified(&config.key_path)?))
+}
+
+// REDIRECTS --------------------------------------------------------------------------------------
+/// Router redirecting all requests to HTTPS on the given port
+pub fn redirect_router(https_port: u16) -> Router {
+    Router::new().fallback(move |req: Request| async move { redirect_to_https(&req, https_port) })
+}
+
+fn redirect_to_https(req: &Request, https_port: u16) -> Response {
+    let Some(authority) = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| host.parse::<Authority>().ok())
+    else {
+        return Error::InvalidRequest("Missing or invalid `Host` header".to_string())
+            .into_response();
+    };
+
+    let host = match https_port {
+        443 => authority.host().to_string(),
+        port => format!("{}:{port}", authority.host()),
+    };
+    let path_and_query = req.uri().path_and_query().map_or("/", |p| p.as_str());
+
+    Redirect::permanent(&format!("https://{host}{path_and_query}")).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+
+    use axum::{body::Body, http::StatusCode};
+
+    use super::*;
+
+    // UNIT TESTS - HELPERS
+    #[test]
+    fn test_redirect_to_https() {
+        let redirect = |uri: &str, host: Option<&str>, https_port| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(host) = host {
+                request = request.header(header::HOST, host);
+            }
+            redirect_to_https(&request.body(Body::empty()).unwrap(), https_port)
+        };
+
+        let response = redirect("/v0/wonders?name=a", Some("example.com:8080"), 8138);
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://example.com:8138/v0/wonders?name=a"
+        );
+
+        let response = redirect("/", Some("[::1]"), 443);
+        assert_eq!(response.headers()[header::LOCATION], "https://[::1]/");
+
+        let response = redirect("/", None, 443);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reload_if_changed() {
+        let dir =
+            std::env::temp_dir().join(format!("world-wonders-api-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = TlsConfig {
+            cert_path: dir.join("cert.pem").to_string_lossy().into_owned(),
+            key_path: dir.join("key.pem").to_string_lossy().into_owned(),
+            redirect_http_port: None,
+        };
+        fs::copy("tests/fixtures/cert.pem", &config.cert_path).unwrap();
+        fs::copy("tests/fixtures/key.pem", &config.key_path).unwrap();
+
+        let rustls_config = load(&config).await.unwrap();
+        let mut modified = modified_times(&config);
+        assert!(!reload_if_changed(&rustls_config, &config, &mut modified).await);
+
+        let set_modified = |path: &str, time| {
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+        let later = SystemTime::now() + Duration::from_secs(60);
+
+        // Invalid files are not loaded, and are retried on the next check
+        fs::write(&config.key_path, "invalid").unwrap();
+        set_modified(&config.key_path, later);
+        assert!(!reload_if_changed(&rustls_config, &config, &mut modified).await);
+
+        fs::copy("tests/fixtures/key.pem", &config.key_path).unwrap();
+        set_modified(&config.key_path, later + Duration::from_secs(60));
+        assert!(reload_if_changed(&rustls_config, &config, &mut modified).await);
+        assert!(!reload_if_changed(&rustls_config, &config, &mut modified).await);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+//! Prometheus metrics about how the API is used, alongside the generic HTTP metrics. Labels only
+//! take values from fixed sets (such as the slugs of existing wonders), so cardinality is bounded
+
+use metrics::{counter, gauge};
+
+use crate::{
+    data::{DATASET_VERSION, WONDERS},
+    error::Error,
+    routes::wonders::{WonderParamsFiltering, WonderParamsSorting},
+};
+
+/// Record the size and version of the loaded dataset
+pub fn record_dataset() {
+    gauge!("dataset_wonders").set(WONDERS.len() as f64);
+    gauge!("dataset_info", "version" => DATASET_VERSION.as_str()).set(1.0);
+}
+
+/// Record a wonder being looked up by its slug
+pub fn record_lookup(slug: &str) {
+    counter!("wonder_lookups_total", "wonder" => slug.to_string()).increment(1);
+}
+
+/// Record which filters were provided
+pub fn record_filters(params: &WonderParamsFiltering) {
+    let filters = [
+        ("name", params.name.is_some()),
+        ("location", params.location.is_some()),
+        ("time_period", params.time_period.is_some()),
+        ("lower_limit", params.lower_limit.is_some()),
+        ("upper_limit", params.upper_limit.is_some()),
+        ("category", params.category.is_some()),
+    ];
+    for (filter, _) in filters.into_iter().filter(|(_, used)| *used) {
+        counter!("wonder_filter_usage_total", "filter" => filter).increment(1);
+    }
+}
+
+/// Record which sort parameters were provided
+pub fn record_sorting(params: &WonderParamsSorting) {
+    if let Some(sort_by) = params.sort_by {
+        counter!(
+            "wonder_sort_usage_total",
+            "sort_by" => format!("{sort_by:?}"),
+            "reverse" => params.sort_reverse.unwrap_or(false).to_string(),
+        )
+        .increment(1);
+    }
+}
+
+/// Record errors for missing wonders, and rejections by the rate limiter
+pub fn record_error(error: &Error) {
+    match error {
+        Error::NoWondersLeft => counter!("wonder_errors_total", "error" => "no_wonders_left"),
+        Error::NoMatchingName(_) => {
+            counter!("wonder_errors_total", "error" => "no_matching_name")
+        }
+        Error::NotEnoughWonders(_, _) => {
+            counter!("wonder_errors_total", "error" => "not_enough_wonders")
+        }
+        Error::TooManyRequests(_) => {
+            counter!("rate_limit_rejections_total", "reason" => "too_many_requests")
+        }
+        Error::QuotaExceeded(_) => {
+            counter!("rate_limit_rejections_total", "reason" => "quota_exceeded")
+        }
+        Error::InvalidApiKey => {
+            counter!("rate_limit_rejections_total", "reason" => "invalid_api_key")
+        }
+        Error::ConflictingLimitParams(_, _) | Error::InvalidRequest(_) | Error::Internal(_) => {
+            return
+        }
+    }
+    .increment(1);
+}
@@ -0,0 +1,215 @@
+use aide::transform::TransformOperation;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    config::VersioningConfig,
+    error::{Error, ErrorResponse, TypedErrorResponse},
+    extractors::Json,
+    V1_DOCS_ROUTE,
+};
+
+/// Prefix of the `/v1` routes
+const V1_PREFIX: &str = "/v1";
+
+/// Versions of the API which are served alongside each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// Original version of the API, now deprecated
+    V0,
+    /// Errors include a machine-readable code, missing wonders give `404` responses, and lists of
+    /// wonders are paginated
+    V1,
+}
+impl ApiVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V0 => "v0",
+            Self::V1 => "v1",
+        }
+    }
+}
+
+/// Document an error response for an operation, in the format used by the given API version
+pub(crate) fn error_docs(
+    op: TransformOperation,
+    version: ApiVersion,
+    error: Error,
+) -> TransformOperation {
+    errors_docs(op, version, &[error])
+}
+
+/// Document the error responses for an operation, in the format used by the given API version.
+/// Errors with the same status are documented as one response, using the first as the example
+pub(crate) fn errors_docs<'a>(
+    mut op: TransformOperation<'a>,
+    version: ApiVersion,
+    errors: &[Error],
+) -> TransformOperation<'a> {
+    for status in [StatusCode::BAD_REQUEST, StatusCode::NOT_FOUND] {
+        let errors: Vec<&Error> = errors
+            .iter()
+            .filter(|error| match version {
+                ApiVersion::V0 => status == StatusCode::BAD_REQUEST,
+                ApiVersion::V1 => {
+                    (error.status_v1() == StatusCode::NOT_FOUND)
+                        == (status == StatusCode::NOT_FOUND)
+                }
+            })
+            .collect();
+        let Some(&example) = errors.first() else {
+            continue;
+        };
+
+        let mut description = match status {
+            StatusCode::NOT_FOUND => "Not found".to_string(),
+            _ => "Bad request".to_string(),
+        };
+        if errors.len() > 1 {
+            description.push_str(", such as:\n");
+            for error in &errors {
+                description.push_str(&format!("\n- {error}"));
+            }
+        }
+
+        op = match (version, status) {
+            (ApiVersion::V0, _) => op.response_with::<400, ErrorResponse, _>(|res| {
+                res.description(&description)
+                    .example(ErrorResponse::new(example))
+            }),
+            (ApiVersion::V1, StatusCode::NOT_FOUND) => op
+                .response_with::<404, TypedErrorResponse, _>(|res| {
+                    res.description(&description)
+                        .example(TypedErrorResponse::from(example))
+                }),
+            (ApiVersion::V1, _) => op.response_with::<400, TypedErrorResponse, _>(|res| {
+                res.description(&description)
+                    .example(TypedErrorResponse::from(example))
+            }),
+        };
+    }
+    op
+}
+
+// MIDDLEWARE -------------------------------------------------------------------------------------
+/// Headers advertising the deprecation of an API version, used as the state for
+/// [`deprecation_headers`]
+#[derive(Clone)]
+pub struct DeprecationHeaders(HeaderMap);
+impl DeprecationHeaders {
+    /// Build the headers for `/v0` from the given configuration
+    pub fn v0(config: &VersioningConfig) -> Self {
+        let mut headers = HeaderMap::new();
+
+        // https://www.rfc-editor.org/rfc/rfc9745
+        if let Some(deprecation) = config.v0_deprecation.as_deref() {
+            let timestamp = httpdate::parse_http_date(deprecation)
+                .expect("Invalid `versioning.v0_deprecation` configuration value")
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("`versioning.v0_deprecation` must be after the UNIX epoch")
+                .as_secs();
+            headers.insert(
+                HeaderName::from_static("deprecation"),
+                HeaderValue::from_str(&format!("@{timestamp}")).unwrap(),
+            );
+            headers.insert(
+                header::LINK,
+                HeaderValue::from_str(&format!("<{V1_DOCS_ROUTE}>; rel=\"successor-version\""))
+                    .unwrap(),
+            );
+        }
+
+        // https://www.rfc-editor.org/rfc/rfc8594
+        if let Some(sunset) = config.v0_sunset.as_deref() {
+            httpdate::parse_http_date(sunset)
+                .expect("Invalid `versioning.v0_sunset` configuration value");
+            headers.insert(
+                HeaderName::from_static("sunset"),
+                HeaderValue::from_str(sunset).unwrap(),
+            );
+        }
+
+        Self(headers)
+    }
+}
+
+/// Middleware adding headers advertising the deprecation of an API version to all responses
+pub async fn deprecation_headers(
+    State(DeprecationHeaders(headers)): State<DeprecationHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    response.headers_mut().extend(headers);
+    response
+}
+
+/// Middleware rendering errors from requests to the `/v1` routes in the format they use. Applied
+/// to all routes, outside of the rate limiting, so its rejections are also rendered. Must be
+/// applied inside of the compression, as it reads and replaces response bodies
+pub async fn typed_errors(req: Request, next: Next) -> Response {
+    let is_v1 = req
+        .uri()
+        .path()
+        .strip_prefix(V1_PREFIX)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+    let mut response = next.run(req).await;
+    if !is_v1 {
+        return response;
+    }
+
+    let error = match response.extensions_mut().remove::<Error>() {
+        Some(error) => error,
+        // Validation rejections from `axum_valid` are plain text
+        None if response.status() == axum_valid::VALIDATION_ERROR_STATUS
+            && response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .is_some_and(|value| value.as_bytes().starts_with(b"text/plain")) =>
+        {
+            let (parts, body) = response.into_parts();
+            let message = axum::body::to_bytes(body, usize::MAX)
+                .await
+                .unwrap_or_default();
+            response = Response::from_parts(parts, axum::body::Body::empty());
+            Error::InvalidRequest(String::from_utf8_lossy(&message).into_owned())
+        }
+        None => return response,
+    };
+
+    let (mut parts, _) = response.into_parts();
+    // Replaced by the new body
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_ENCODING);
+
+    let mut typed = (error.status_v1(), Json(TypedErrorResponse::from(&error))).into_response();
+    typed.headers_mut().extend(parts.headers);
+    typed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecation_headers() {
+        let DeprecationHeaders(headers) = DeprecationHeaders::v0(&VersioningConfig {
+            v0_deprecation: Some("Sun, 18 Oct 2026 00:00:00 GMT".to_string()),
+            v0_sunset: Some("Mon, 18 Oct 2027 00:00:00 GMT".to_string()),
+        });
+        assert_eq!(headers["deprecation"], "@1792281600");
+        assert_eq!(headers["sunset"], "Mon, 18 Oct 2027 00:00:00 GMT");
+        assert_eq!(headers["link"], "</v1/docs>; rel=\"successor-version\"");
+
+        let DeprecationHeaders(headers) = DeprecationHeaders::v0(&VersioningConfig {
+            v0_deprecation: None,
+            v0_sunset: None,
+        });
+        assert!(headers.is_empty());
+    }
+}
@@ -1,10 +1,19 @@
 use std::net::SocketAddr;
 
 use axum_test::TestServer;
-use world_wonders_api::get_app;
+use world_wonders_api::{
+    config::{get_config, Config},
+    get_app,
+};
 
 /// Get a test server using the router that will be used for the actual server
 pub fn get_server() -> TestServer {
-    let app = get_app().into_make_service_with_connect_info::<SocketAddr>();
+    get_server_with_config(get_config().expect("Failed to read configuration"))
+}
+
+/// Get a test server using the router that will be used for the actual server, with the given
+/// configuration
+pub fn get_server_with_config(config: Config) -> TestServer {
+    let app = get_app(&config).into_make_service_with_connect_info::<SocketAddr>();
     TestServer::new(app).unwrap()
 }
@@ -0,0 +1,211 @@
+use pretty_assertions::assert_eq;
+use tonic::{
+    transport::{Certificate, Channel, ClientTlsConfig},
+    Code,
+};
+use world_wonders_api::{
+    config::TlsConfig,
+    data::WONDERS,
+    grpc::{
+        proto::{
+            wonders_client::WondersClient, Category, Filter, FilterRequest, GetRequest,
+            ListRequest, RandomRequest, Sort, SortBy, TimePeriod, Weights,
+        },
+        serve,
+    },
+    tls,
+};
+
+/// Start the gRPC server on a random port, and get a client connected to it
+async fn get_client() -> WondersClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener, None, std::future::pending()));
+
+    WondersClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap()
+}
+
+fn seven_wonders() -> Option<Filter> {
+    Some(Filter {
+        category: Some(Category::SevenWonders.into()),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_grpc() {
+    let mut client = get_client().await;
+
+    // List
+    let wonders = client
+        .list(ListRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .wonders;
+    assert_eq!(wonders.len(), WONDERS.len());
+    assert_eq!(wonders[0].name, WONDERS[0].name);
+
+    let wonders = client
+        .list(ListRequest {
+            filter: seven_wonders(),
+            sort: Some(Sort {
+                sort_by: Some(SortBy::BuildYear.into()),
+                sort_reverse: Some(true),
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .wonders;
+    assert_eq!(wonders.len(), 7);
+    wonders.iter().reduce(|a, b| {
+        assert!(a.build_year >= b.build_year);
+        b
+    });
+
+    // Count
+    let count = client
+        .count(FilterRequest {
+            filter: seven_wonders(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .count;
+    assert_eq!(count, 7);
+
+    // Get
+    let wonder = client
+        .get(GetRequest {
+            name: "alhambra".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(wonder.name, "Alhambra");
+
+    let status = client
+        .get(GetRequest {
+            name: "a".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    // Random
+    let wonder = client
+        .random(RandomRequest {
+            filter: seven_wonders(),
+            weights: Some(Weights {
+                weight_by_popularity: Some(true),
+                favour_time_period: Some(TimePeriod::Ancient.into()),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(wonder.categories.contains(&Category::SevenWonders.into()));
+
+    // Oldest & youngest
+    let oldest = client
+        .oldest(FilterRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let youngest = client
+        .youngest(FilterRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        oldest.build_year,
+        WONDERS.iter().map(|w| w.build_year).min().unwrap() as i32
+    );
+    assert_eq!(
+        youngest.build_year,
+        WONDERS.iter().map(|w| w.build_year).max().unwrap() as i32
+    );
+
+    // Errors
+    let status = client
+        .count(FilterRequest {
+            filter: Some(Filter {
+                lower_limit: Some(500),
+                upper_limit: Some(400),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let status = client
+        .random(RandomRequest {
+            filter: Some(Filter {
+                name: Some("abcdefghijk".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let status = client
+        .oldest(FilterRequest {
+            filter: Some(Filter {
+                lower_limit: Some(i32::MAX),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_grpc_tls() {
+    let tls_config = TlsConfig {
+        cert_path: "tests/fixtures/cert.pem".to_string(),
+        key_path: "tests/fixtures/key.pem".to_string(),
+        redirect_http_port: None,
+    };
+    let rustls_config = tls::load(&tls_config).await.unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener, Some(rustls_config), std::future::pending()));
+
+    // Client trusting the self-signed test certificate
+    let channel = Channel::from_shared(format!("https://{addr}"))
+        .unwrap()
+        .tls_config(
+            ClientTlsConfig::new()
+                .ca_certificate(Certificate::from_pem(
+                    std::fs::read(&tls_config.cert_path).unwrap(),
+                ))
+                .domain_name("localhost"),
+        )
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let wonder = WondersClient::new(channel)
+        .get(GetRequest {
+            name: "colosseum".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(wonder.name, "Colosseum");
+
+    // Plaintext connections are rejected
+    let mut client = WondersClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    assert!(client.list(ListRequest::default()).await.is_err());
+}
@@ -0,0 +1,67 @@
+#![cfg(unix)]
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use world_wonders_api::{
+    config::{get_config, UnixSocketConfig},
+    get_app,
+    server::{bind, serve_listeners, Listener},
+    HEALTH_ROUTE,
+};
+
+/// Make a `GET` request to the health route over the given stream, returning the raw response
+async fn get_health(mut stream: impl AsyncRead + AsyncWrite + Unpin) -> String {
+    stream
+        .write_all(
+            format!("GET {HEALTH_ROUTE} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_listeners() {
+    let socket_path = std::env::temp_dir().join(format!(
+        "world-wonders-api-listeners-{}.sock",
+        std::process::id()
+    ));
+
+    let mut config = get_config().unwrap();
+    config.network.host = "127.0.0.1".to_string();
+    config.network.port = 0;
+    config.network.binds = vec!["localhost:0".to_string()];
+    config.network.unix_socket = Some(UnixSocketConfig {
+        path: socket_path.to_string_lossy().into_owned(),
+        mode: None,
+    });
+
+    let listeners = bind(&config.network).unwrap();
+    let tcp_addrs: Vec<_> = listeners
+        .iter()
+        .filter_map(|listener| match listener {
+            Listener::Tcp(listener) => Some(listener.local_addr().unwrap()),
+            Listener::Unix(_) => None,
+        })
+        .collect();
+    assert_eq!(tcp_addrs.len(), 2);
+
+    tokio::spawn(serve_listeners(get_app(&config), listeners, None));
+
+    for addr in tcp_addrs {
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let response = get_health(stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Healthy!"));
+    }
+
+    let stream = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+    let response = get_health(stream).await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("Healthy!"));
+
+    std::fs::remove_file(socket_path).unwrap();
+}
@@ -1,16 +1,16 @@
+use std::net::ToSocketAddrs;
+
 use anyhow::Result;
-use world_wonders_api::{
-    config::{get_config, NetworkConfig},
-    WONDERS_ROUTE,
-};
+use world_wonders_api::{config::get_config, WONDERS_ROUTE};
 
 #[ignore = "Only used for convenient development"]
 #[tokio::test]
 async fn quick_dev() -> Result<()> {
-    let NetworkConfig { host, port } = get_config().expect("Failed to read configuration").network;
+    let network = get_config().expect("Failed to read configuration").network;
+    let (host, port) = network.host_addr(network.port);
     let hc = httpc_test::new_client(format!(
-        "http://{}.{}.{}.{}:{port}",
-        host[0], host[1], host[2], host[3]
+        "http://{}",
+        (host, port).to_socket_addrs()?.next().unwrap()
     ))?;
 
     hc.do_get(WONDERS_ROUTE).await?.print().await?;
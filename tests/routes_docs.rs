@@ -1,4 +1,6 @@
-use world_wonders_api::DOCS_ROUTE;
+use axum::http::{header, HeaderValue};
+use serde_json::Value;
+use world_wonders_api::{DOCS_ROUTE, HEALTH_LIVE_ROUTE, HEALTH_READY_ROUTE, HEALTH_ROUTE};
 
 mod common;
 use common::get_server;
@@ -19,4 +21,37 @@ async fn test_routes_docs() {
             .unwrap()
             > 1000000
     );
+
+    // Health responses, of which the liveness response is inferred from the handler
+    let api = server
+        .get(&format!("{DOCS_ROUTE}/api.json"))
+        .await
+        .json::<Value>();
+    let responses = |route: &str| api["paths"][route]["get"]["responses"].clone();
+    assert!(responses(HEALTH_LIVE_ROUTE)["200"].is_object());
+    assert!(responses(HEALTH_READY_ROUTE)["200"].is_object());
+    assert!(responses(HEALTH_READY_ROUTE)["503"].is_object());
+}
+
+#[tokio::test]
+async fn test_routes_docs_compression() {
+    let server = get_server();
+
+    for encoding in ["gzip", "br", "zstd"] {
+        let response = server
+            .get(DOCS_ROUTE)
+            .add_header(header::ACCEPT_ENCODING, HeaderValue::from_static(encoding))
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-encoding"), encoding);
+        assert!(response.as_bytes().len() < 1000000);
+    }
+
+    // Small responses aren't compressed
+    let response = server
+        .get(HEALTH_ROUTE)
+        .add_header(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip"))
+        .await;
+    response.assert_status_ok();
+    assert!(response.maybe_header("content-encoding").is_none());
 }
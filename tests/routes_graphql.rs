@@ -0,0 +1,38 @@
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use world_wonders_api::GRAPHQL_ROUTE;
+
+mod common;
+use common::get_server;
+
+#[tokio::test]
+async fn test_routes_graphql() {
+    let server = get_server();
+
+    // Playground
+    let response = server.get(GRAPHQL_ROUTE).await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "text/html; charset=utf-8");
+    response.assert_text_contains("graphiql");
+
+    // Query
+    let response = server
+        .post(GRAPHQL_ROUTE)
+        .json(&json!({ "query": "{ count(filter: { category: SEVEN_WONDERS }) }" }))
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&json!({ "data": { "count": 7 } }));
+
+    // Errors
+    let response = server
+        .post(GRAPHQL_ROUTE)
+        .json(&json!({ "query": "{ wonder(name: \"a\") { name } }" }))
+        .await;
+    response.assert_status_ok();
+    let body = response.json::<Value>();
+    assert_eq!(body["data"], Value::Null);
+    assert_eq!(
+        body["errors"][0]["message"],
+        "No wonder found matching the name 'a'"
+    );
+}
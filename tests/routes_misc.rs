@@ -1,8 +1,16 @@
+use std::num::{NonZeroU32, NonZeroU64};
+
+use axum::http::{Method, StatusCode};
 use pretty_assertions::assert_eq;
-use world_wonders_api::{HEALTH_ROUTE, METRICS_ROUTE};
+use serde_json::Value;
+use world_wonders_api::{
+    config::{get_config, ApiKeyConfig, RouteRateLimitConfig},
+    data::{DATASET_VERSION, WONDERS},
+    DOCS_ROUTE, HEALTH_LIVE_ROUTE, HEALTH_READY_ROUTE, HEALTH_ROUTE, METRICS_ROUTE, WONDERS_ROUTE,
+};
 
 mod common;
-use common::get_server;
+use common::{get_server, get_server_with_config};
 
 #[tokio::test]
 async fn test_routes_misc() {
@@ -32,3 +40,260 @@ async fn test_routes_misc() {
             > 1000
     );
 }
+
+#[tokio::test]
+async fn test_usage_metrics() {
+    let server = get_server();
+
+    server
+        .get(&format!("{WONDERS_ROUTE}/name/alhambra"))
+        .await
+        .assert_status_ok();
+    server
+        .get(&format!("{WONDERS_ROUTE}/name/not-a-wonder"))
+        .await
+        .assert_status_bad_request();
+    server
+        .get(&format!(
+            "{WONDERS_ROUTE}?category=SevenWonders&sort_by=BuildYear"
+        ))
+        .await
+        .assert_status_ok();
+
+    let metrics = server.get(METRICS_ROUTE).await.text();
+    for line in [
+        "wonder_lookups_total{wonder=\"alhambra\"}",
+        "wonder_errors_total{error=\"no_matching_name\"}",
+        "wonder_filter_usage_total{filter=\"category\"}",
+        "wonder_sort_usage_total{sort_by=\"BuildYear\",reverse=\"false\"}",
+    ] {
+        assert!(metrics.contains(line), "Missing {line}");
+    }
+    assert!(!metrics.contains("wonder=\"not-a-wonder\""));
+}
+
+#[tokio::test]
+async fn test_routes_health() {
+    let server = get_server();
+
+    let response = server.get(HEALTH_LIVE_ROUTE).await;
+    response.assert_status_ok();
+    let health = response.json::<Value>();
+    assert_eq!(health["build"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(health["dataset"]["checksum"], DATASET_VERSION.as_str());
+    assert!(health["uptime_seconds"].is_u64());
+
+    // The dataset is loaded in the background
+    let mut response = server.get(HEALTH_READY_ROUTE).await;
+    while response.status_code() == StatusCode::SERVICE_UNAVAILABLE {
+        assert_eq!(response.json::<Value>()["ready"], false);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        response = server.get(HEALTH_READY_ROUTE).await;
+    }
+    response.assert_status_ok();
+    let health = response.json::<Value>();
+    assert_eq!(health["ready"], true);
+    assert_eq!(health["dataset"]["status"], "loaded");
+    assert_eq!(health["dataset"]["wonder_count"], WONDERS.len());
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    let mut config = get_config().unwrap();
+    config.rate_limit.burst_size = NonZeroU32::new(2).unwrap();
+    config.rate_limit.per_millisecond = NonZeroU64::new(60_000).unwrap();
+    config.rate_limit.routes = vec![RouteRateLimitConfig {
+        prefix: WONDERS_ROUTE.to_string(),
+        burst_size: NonZeroU32::new(1).unwrap(),
+        per_millisecond: NonZeroU64::new(60_000).unwrap(),
+    }];
+    let server = get_server_with_config(config);
+
+    let response = server.get(HEALTH_ROUTE).await;
+    response.assert_status_ok();
+    assert_eq!(response.header("ratelimit-limit"), "2");
+    assert_eq!(response.header("ratelimit-remaining"), "1");
+    assert_eq!(response.header("ratelimit-reset"), "60");
+    assert!(response.maybe_header("retry-after").is_none());
+
+    server.get(HEALTH_ROUTE).await.assert_status_ok();
+
+    let response = server.get(HEALTH_ROUTE).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.header("ratelimit-remaining"), "0");
+    assert_eq!(response.header("retry-after"), "60");
+    assert_eq!(
+        response.json::<Value>(),
+        serde_json::json!({
+            "message": "Too many requests, retry after 60 seconds",
+            "request_id": response.header("x-request-id").to_str().unwrap(),
+        })
+    );
+
+    // Overridden routes are limited separately
+    let response = server.get(WONDERS_ROUTE).await;
+    response.assert_status_ok();
+    assert_eq!(response.header("ratelimit-limit"), "1");
+    assert_eq!(response.header("ratelimit-remaining"), "0");
+    server
+        .get(WONDERS_ROUTE)
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_api_keys() {
+    let mut config = get_config().unwrap();
+    config.rate_limit.burst_size = NonZeroU32::new(1).unwrap();
+    config.rate_limit.per_millisecond = NonZeroU64::new(60_000).unwrap();
+    config.api_keys.keys = vec![ApiKeyConfig {
+        name: "batch".to_string(),
+        key: "abc".to_string(),
+        burst_size: NonZeroU32::new(10).unwrap(),
+        per_millisecond: NonZeroU64::new(60_000).unwrap(),
+        daily_quota: Some(2),
+    }];
+    let server = get_server_with_config(config);
+
+    // Limited separately from anonymous requests
+    server.get(HEALTH_ROUTE).await.assert_status_ok();
+    server
+        .get(HEALTH_ROUTE)
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+    let response = server
+        .get(WONDERS_ROUTE)
+        .add_header("x-api-key", "abc")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("ratelimit-limit"), "10");
+    assert_eq!(response.header("ratelimit-remaining"), "9");
+    assert_eq!(response.header("x-quota-limit"), "2");
+    assert_eq!(response.header("x-quota-remaining"), "1");
+
+    let response = server
+        .get(&format!(
+            "{WONDERS_ROUTE}?category=SevenWonders&api_key=abc"
+        ))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("x-quota-remaining"), "0");
+
+    let response = server
+        .get(HEALTH_ROUTE)
+        .add_header("x-api-key", "abc")
+        .await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        response.header("retry-after"),
+        response.header("x-quota-reset")
+    );
+    assert!(response.json::<Value>()["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Daily quota exceeded"));
+
+    // Unknown keys are rejected rather than treated as anonymous
+    let response = server.get(&format!("{HEALTH_ROUTE}?api_key=def")).await;
+    response.assert_status_unauthorized();
+    assert_eq!(
+        response.json::<Value>(),
+        serde_json::json!({
+            "message": "Invalid API key",
+            "request_id": response.header("x-request-id").to_str().unwrap(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_security_headers() {
+    let server = get_server();
+
+    for route in [HEALTH_ROUTE, DOCS_ROUTE, "not-a-route"] {
+        let response = server.get(route).await;
+        assert_eq!(response.header("x-content-type-options"), "nosniff");
+        assert_eq!(response.header("referrer-policy"), "no-referrer");
+        assert!(response
+            .header("content-security-policy")
+            .to_str()
+            .unwrap()
+            .starts_with("default-src 'self'"));
+        // Only enabled when served over HTTPS
+        assert!(response.maybe_header("strict-transport-security").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_cors() {
+    let mut config = get_config().unwrap();
+    config.cors.allowed_origins = vec!["https://example.com".to_string()];
+    let server = get_server_with_config(config);
+
+    let response = server
+        .method(Method::OPTIONS, WONDERS_ROUTE)
+        .add_header("origin", "https://example.com")
+        .add_header("access-control-request-method", "GET")
+        .add_header("access-control-request-headers", "x-api-key")
+        .await;
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "https://example.com"
+    );
+    assert_eq!(response.header("access-control-max-age"), "3600");
+    assert!(response
+        .header("access-control-allow-headers")
+        .to_str()
+        .unwrap()
+        .contains("x-api-key"));
+
+    let response = server
+        .get(WONDERS_ROUTE)
+        .add_header("origin", "https://example.com")
+        .await;
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "https://example.com"
+    );
+    assert!(response
+        .header("access-control-expose-headers")
+        .to_str()
+        .unwrap()
+        .contains("retry-after"));
+
+    // Origins not on the allow-list don't get CORS headers
+    let response = server
+        .get(WONDERS_ROUTE)
+        .add_header("origin", "https://example.org")
+        .await;
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_request_id() {
+    let server = get_server();
+
+    // Generated if not provided
+    let first = server.get(HEALTH_ROUTE).await.header("x-request-id");
+    let second = server.get(HEALTH_ROUTE).await.header("x-request-id");
+    assert_eq!(first.len(), 36);
+    assert_ne!(first, second);
+
+    // Provided IDs are echoed, and included in error responses
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/name/a"))
+        .add_header("x-request-id", "client-id-123")
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.header("x-request-id"), "client-id-123");
+    assert_eq!(response.json::<Value>()["request_id"], "client-id-123");
+
+    // Invalid IDs are replaced
+    let response = server
+        .get(HEALTH_ROUTE)
+        .add_header("x-request-id", "has spaces")
+        .await;
+    assert_eq!(response.header("x-request-id").len(), 36);
+}
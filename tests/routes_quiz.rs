@@ -0,0 +1,44 @@
+use pretty_assertions::assert_eq;
+use world_wonders_api::{
+    routes::quiz::{QuestionType, Quiz},
+    QUIZ_ROUTE,
+};
+
+mod common;
+use common::get_server;
+
+#[tokio::test]
+async fn test_routes_quiz() {
+    let server = get_server();
+
+    let response = server
+        .get(&format!(
+            "{QUIZ_ROUTE}?count=3&question_type=identify_from_summary&seed=42"
+        ))
+        .await;
+    response.assert_status_ok();
+    let quiz = response.json::<Quiz>();
+    assert_eq!(quiz.seed, 42);
+    assert_eq!(quiz.questions.len(), 3);
+    for question in &quiz.questions {
+        let summary = question.summary.as_deref().unwrap();
+        assert!(!summary.contains(&question.choices[question.answer]));
+    }
+
+    // Same seed, same quiz
+    let response = server
+        .get(&format!(
+            "{QUIZ_ROUTE}?count=3&question_type=identify_from_summary&seed=42"
+        ))
+        .await;
+    assert_eq!(response.json::<Quiz>().questions, quiz.questions);
+
+    let response = server
+        .get(&format!("{QUIZ_ROUTE}?difficulty=impossible"))
+        .await;
+    response.assert_status_bad_request();
+
+    let response = server.get(&format!("{QUIZ_ROUTE}/question-types")).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Vec<QuestionType>>().len(), 5);
+}
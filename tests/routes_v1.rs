@@ -0,0 +1,246 @@
+use std::num::{NonZeroU32, NonZeroU64};
+
+use axum::http::{header, HeaderValue, StatusCode};
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use world_wonders_api::{
+    config::{get_config, ApiKeyConfig},
+    data::{Wonder, WONDERS},
+    error::{ErrorCode, TypedErrorResponse},
+    routes::wonders::Page,
+    DOCS_ROUTE, HEALTH_ROUTE, V1_DOCS_ROUTE, V1_WONDERS_ROUTE, WONDERS_ROUTE,
+};
+
+mod common;
+use common::{get_server, get_server_with_config};
+
+#[tokio::test]
+async fn test_routes_v1() {
+    let server = get_server();
+
+    // Paginated list
+    let response = server.get(V1_WONDERS_ROUTE).await;
+    response.assert_status_ok();
+    let page = response.json::<Page<Wonder>>();
+    assert_eq!(page.items.len(), 20);
+    assert_eq!(page.items[0], WONDERS[0]);
+    assert_eq!(page.page, 1);
+    assert_eq!(page.total, WONDERS.len());
+    assert_eq!(page.total_pages, WONDERS.len().div_ceil(20));
+
+    let response = server
+        .get(&format!(
+            "{V1_WONDERS_ROUTE}?category=SevenWonders&page=2&per_page=5"
+        ))
+        .await;
+    response.assert_status_ok();
+    let page = response.json::<Page<Wonder>>();
+    assert_eq!(page.items.len(), 2);
+    assert_eq!((page.total, page.total_pages), (7, 2));
+
+    // Out of range pages are empty
+    let response = server.get(&format!("{V1_WONDERS_ROUTE}?page=100")).await;
+    response.assert_status_ok();
+    assert!(response.json::<Page<Wonder>>().items.is_empty());
+
+    // Unchanged routes
+    let response = server
+        .get(&format!("{V1_WONDERS_ROUTE}/name/alhambra"))
+        .await;
+    response.assert_status_ok();
+    response.assert_json::<Wonder>(WONDERS.iter().find(|w| w.name == "Alhambra").unwrap());
+    assert!(response.maybe_header("deprecation").is_none());
+}
+
+#[tokio::test]
+async fn test_routes_v1_typed_errors() {
+    let server = get_server();
+
+    let response = server.get(&format!("{V1_WONDERS_ROUTE}/name/a")).await;
+    response.assert_status_not_found();
+    let error = response.json::<TypedErrorResponse>();
+    assert_eq!(error.code, ErrorCode::NoMatchingName);
+    assert_eq!(error.message, "No wonder found matching the name 'a'");
+
+    let response = server
+        .get(&format!("{V1_WONDERS_ROUTE}/random?name=abcdefghijk"))
+        .await;
+    response.assert_status_not_found();
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::NoWondersLeft
+    );
+
+    let response = server
+        .get(&format!(
+            "{V1_WONDERS_ROUTE}?lower_limit=500&upper_limit=400"
+        ))
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::ConflictingLimitParams
+    );
+
+    let response = server
+        .get(&format!(
+            "{V1_WONDERS_ROUTE}/random?count=8&category=SevenWonders"
+        ))
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::NotEnoughWonders
+    );
+
+    // Grouping isn't supported for pages, rather than being ignored
+    let response = server
+        .get(&format!("{V1_WONDERS_ROUTE}?group_by=category"))
+        .await;
+    response.assert_status_bad_request();
+    let error = response.json::<TypedErrorResponse>();
+    assert_eq!(error.code, ErrorCode::InvalidRequest);
+    assert!(error.message.contains("group_by"));
+    server
+        .get(&format!("{V1_WONDERS_ROUTE}/count?group_by=category"))
+        .await
+        .assert_status_ok();
+
+    // Extractor rejections
+    let response = server.get(&format!("{V1_WONDERS_ROUTE}?per_page=0")).await;
+    response.assert_status_bad_request();
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::InvalidRequest
+    );
+
+    // `/v0` errors are unchanged
+    let response = server.get(&format!("{WONDERS_ROUTE}/name/a")).await;
+    response.assert_status_bad_request();
+    assert_eq!(
+        response.json::<Value>(),
+        serde_json::json!({
+            "message": "No wonder found matching the name 'a'",
+            "request_id": response.header("x-request-id").to_str().unwrap(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_routes_v1_compressed_errors() {
+    let mut config = get_config().unwrap();
+    config.compression.min_size = 0;
+    let server = get_server_with_config(config);
+
+    // Errors are rendered before being compressed
+    let response = server
+        .get(&format!("{V1_WONDERS_ROUTE}?per_page=0"))
+        .add_header(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip"))
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.header("content-encoding"), "gzip");
+    assert_eq!(response.header("content-type"), "application/json");
+    // Gzip magic bytes
+    assert!(response.as_bytes().starts_with(&[0x1f, 0x8b]));
+}
+
+#[tokio::test]
+async fn test_routes_v1_rate_limit_errors() {
+    let mut config = get_config().unwrap();
+    config.rate_limit.burst_size = NonZeroU32::new(1).unwrap();
+    config.rate_limit.per_millisecond = NonZeroU64::new(60_000).unwrap();
+    let server = get_server_with_config(config);
+
+    server.get(V1_WONDERS_ROUTE).await.assert_status_ok();
+    let response = server.get(V1_WONDERS_ROUTE).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.header("retry-after"), "60");
+    assert_eq!(response.header("ratelimit-remaining"), "0");
+    let error = response.json::<TypedErrorResponse>();
+    assert_eq!(error.code, ErrorCode::TooManyRequests);
+    assert_eq!(
+        error.request_id.as_deref(),
+        response.header("x-request-id").to_str().ok()
+    );
+
+    // `/v0` rejections are unchanged
+    let response = server.get(WONDERS_ROUTE).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.json::<Value>().get("code").is_none());
+}
+
+#[tokio::test]
+async fn test_routes_v1_api_key_errors() {
+    let mut config = get_config().unwrap();
+    config.api_keys.keys = vec![ApiKeyConfig {
+        name: "batch".to_string(),
+        key: "abc".to_string(),
+        burst_size: NonZeroU32::new(10).unwrap(),
+        per_millisecond: NonZeroU64::new(60_000).unwrap(),
+        daily_quota: Some(1),
+    }];
+    let server = get_server_with_config(config);
+
+    let response = server.get(&format!("{V1_WONDERS_ROUTE}?api_key=def")).await;
+    response.assert_status_unauthorized();
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::InvalidApiKey
+    );
+
+    server
+        .get(&format!("{V1_WONDERS_ROUTE}?api_key=abc"))
+        .await
+        .assert_status_ok();
+    let response = server.get(&format!("{V1_WONDERS_ROUTE}?api_key=abc")).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        response.json::<TypedErrorResponse>().code,
+        ErrorCode::QuotaExceeded
+    );
+}
+
+#[tokio::test]
+async fn test_routes_v0_deprecation() {
+    let server = get_server();
+
+    for route in [WONDERS_ROUTE, DOCS_ROUTE] {
+        let response = server.get(route).await;
+        response.assert_status_ok();
+        assert_eq!(response.header("deprecation"), "@1792281600");
+        assert_eq!(response.header("sunset"), "Mon, 18 Oct 2027 00:00:00 GMT");
+        assert_eq!(
+            response.header("link"),
+            "</v1/docs>; rel=\"successor-version\""
+        );
+    }
+
+    // Unversioned routes aren't deprecated
+    let response = server.get(HEALTH_ROUTE).await;
+    assert!(response.maybe_header("deprecation").is_none());
+}
+
+#[tokio::test]
+async fn test_routes_docs_versions() {
+    let server = get_server();
+
+    let v0 = server
+        .get(&format!("{DOCS_ROUTE}/api.json"))
+        .await
+        .json::<Value>();
+    let v1 = server
+        .get(&format!("{V1_DOCS_ROUTE}/api.json"))
+        .await
+        .json::<Value>();
+
+    assert_eq!(v0["info"]["version"], "v0");
+    assert_eq!(v1["info"]["version"], "v1");
+    assert!(v0["paths"].get("/v0/wonders/").is_some());
+    assert!(v0["paths"].get("/v1/wonders/").is_none());
+    assert!(v1["paths"].get("/v1/wonders/").is_some());
+    assert!(v1["paths"].get("/v0/wonders/").is_none());
+
+    let response = server.get(V1_DOCS_ROUTE).await;
+    response.assert_status_ok();
+    response.assert_text_contains("/v1/docs/api.json");
+}
@@ -1,8 +1,9 @@
-use pretty_assertions::assert_eq;
+use axum::http::{header, HeaderValue, StatusCode};
+use pretty_assertions::{assert_eq, assert_ne};
 use strum::IntoEnumIterator;
 use world_wonders_api::{
     data::{Category, TimePeriod, Wonder, WONDERS},
-    routes::wonders::SortBy,
+    routes::wonders::{RelatedWonder, SortBy, TimelineBucket, WonderComparison, WonderStats},
     WONDERS_ROUTE,
 };
 
@@ -73,3 +74,191 @@ async fn test_routes_wonders() {
             .unwrap(),
     );
 }
+
+#[tokio::test]
+async fn test_routes_wonders_aggregates() {
+    let server = get_server();
+
+    // Grouped
+    let response = server
+        .get(&format!(
+            "{WONDERS_ROUTE}?group_by=century&sort_by=BuildYear"
+        ))
+        .await;
+    response.assert_status_ok();
+    let groups = response.json::<serde_json::Map<String, serde_json::Value>>();
+    let (first_century, first_group) = groups.iter().next().unwrap();
+    assert!(first_century.ends_with("century BCE"));
+    assert_eq!(
+        first_group[0]["name"],
+        WONDERS
+            .iter()
+            .min_by_key(|w| w.build_year)
+            .unwrap()
+            .name
+            .as_str()
+    );
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/count?group_by=category"))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["SevenWonders"], 7);
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}?group_by=not-a-group"))
+        .await;
+    response.assert_status_bad_request();
+
+    // Timeline
+    let response = server
+        .get(&format!(
+            "{WONDERS_ROUTE}/timeline?resolution=millennium&category=SevenWonders"
+        ))
+        .await;
+    response.assert_status_ok();
+    let timeline = response.json::<Vec<TimelineBucket<Wonder>>>();
+    assert_eq!(timeline[0].label, "3rd millennium BCE");
+    assert_eq!(timeline.iter().map(|b| b.wonders.len()).sum::<usize>(), 7);
+
+    // Stats
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/stats?time_period=Modern"))
+        .await;
+    response.assert_status_ok();
+    let stats = response.json::<WonderStats>();
+    assert_eq!(
+        stats.count,
+        WONDERS
+            .iter()
+            .filter(|w| w.time_period == TimePeriod::Modern)
+            .count()
+    );
+    assert_eq!(stats.by_time_period[&TimePeriod::Modern], stats.count);
+    assert!(stats.build_year.unwrap().min > 1800);
+
+    // Comparison
+    let response = server
+        .get(&format!(
+            "{WONDERS_ROUTE}/compare?a=great-pyramid-of-giza&b=stonehenge"
+        ))
+        .await;
+    response.assert_status_ok();
+    let comparison = response.json::<WonderComparison<Wonder>>();
+    assert_eq!(comparison.a.name, "Great Pyramid of Giza");
+    assert_eq!(comparison.b.name, "Stonehenge");
+    assert!(comparison.distance_km.is_some());
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/compare?a=stonehenge&b=a"))
+        .await;
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_routes_wonders_conditional_get() {
+    let server = get_server();
+    let route = format!("{WONDERS_ROUTE}?category=SevenWonders");
+
+    let response = server.get(&route).await;
+    response.assert_status_ok();
+    let etag = response.header("etag");
+    let last_modified = response.header("last-modified");
+    assert_eq!(response.header("cache-control"), "public, max-age=3600");
+
+    // Same query, same tag
+    assert_eq!(server.get(&route).await.header("etag"), etag);
+    // Different query, different tag
+    assert_ne!(server.get(WONDERS_ROUTE).await.header("etag"), etag);
+
+    // If-None-Match
+    let response = server
+        .get(&route)
+        .add_header(header::IF_NONE_MATCH, etag.clone())
+        .await;
+    response.assert_status(StatusCode::NOT_MODIFIED);
+    assert_eq!(response.header("etag"), etag);
+    assert!(response.as_bytes().is_empty());
+
+    let response = server
+        .get(&route)
+        .add_header(header::IF_NONE_MATCH, HeaderValue::from_static("\"abc\""))
+        .await;
+    response.assert_status_ok();
+
+    // If-Modified-Since
+    let response = server
+        .get(&route)
+        .add_header(header::IF_MODIFIED_SINCE, last_modified.clone())
+        .await;
+    response.assert_status(StatusCode::NOT_MODIFIED);
+
+    // Errors are never replaced by 304s
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/name/not-a-wonder"))
+        .add_header(header::IF_NONE_MATCH, HeaderValue::from_static("*"))
+        .await;
+    response.assert_status_bad_request();
+    assert!(response.maybe_header("etag").is_none());
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}?lower_limit=500&upper_limit=400"))
+        .add_header(header::IF_MODIFIED_SINCE, last_modified)
+        .await;
+    response.assert_status_bad_request();
+
+    // Random wonders shouldn't be cached
+    let response = server.get(&format!("{WONDERS_ROUTE}/random")).await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-store");
+    assert!(response.maybe_header("etag").is_none());
+}
+
+#[tokio::test]
+async fn test_routes_wonders_daily() {
+    let server = get_server();
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/daily?date=2025-01-01"))
+        .await;
+    response.assert_status_ok();
+    let wonder = response.json::<Wonder>();
+    assert!(WONDERS.contains(&wonder));
+
+    // Each day of a cycle gets a different wonder
+    let mut names = std::collections::HashSet::new();
+    for day in 1..=5 {
+        let response = server
+            .get(&format!(
+                "{WONDERS_ROUTE}/daily?date=2025-02-0{day}&time_period=Ancient"
+            ))
+            .await;
+        response.assert_status_ok();
+        names.insert(response.json::<Wonder>().name);
+    }
+    assert!(names.len() > 1);
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/daily?date=tomorrow"))
+        .await;
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_routes_wonders_related() {
+    let server = get_server();
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/name/taj-mahal/related?limit=3"))
+        .await;
+    response.assert_status_ok();
+    let related = response.json::<Vec<RelatedWonder<Wonder>>>();
+    assert_eq!(related.len(), 3);
+    assert!(related.iter().all(|r| r.wonder.name != "Taj Mahal"));
+    assert!(related[0].score >= related[2].score);
+
+    let response = server
+        .get(&format!("{WONDERS_ROUTE}/name/not-a-wonder/related"))
+        .await;
+    response.assert_status_bad_request();
+}
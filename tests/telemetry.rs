@@ -0,0 +1,118 @@
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::{
+        trace_service_server::{TraceService, TraceServiceServer},
+        ExportTraceServiceRequest, ExportTraceServiceResponse,
+    },
+    common::v1::any_value::Value,
+    trace::v1::Span,
+};
+use pretty_assertions::assert_eq;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+use world_wonders_api::{
+    config::{get_config, LogFormat},
+    get_app, telemetry, GRAPHQL_ROUTE, HEALTH_ROUTE, WONDERS_ROUTE,
+};
+
+/// Stand-in for an OpenTelemetry collector, forwarding the exported spans
+struct Collector(mpsc::UnboundedSender<Span>);
+
+#[tonic::async_trait]
+impl TraceService for Collector {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        let spans = request
+            .into_inner()
+            .resource_spans
+            .into_iter()
+            .flat_map(|resource_spans| resource_spans.scope_spans)
+            .flat_map(|scope_spans| scope_spans.spans);
+        for span in spans {
+            self.0.send(span).unwrap();
+        }
+
+        Ok(Response::new(ExportTraceServiceResponse::default()))
+    }
+}
+
+/// Start a collector on a random port, returning its endpoint and the spans it receives
+async fn start_collector() -> (String, mpsc::UnboundedReceiver<Span>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(TraceServiceServer::new(Collector(tx)))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    (format!("http://{addr}"), rx)
+}
+
+fn attribute(span: &Span, key: &str) -> Option<Value> {
+    span.attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .and_then(|attribute| attribute.value.clone())
+        .and_then(|value| value.value)
+}
+
+// Exporting spans in batches needs a multi-threaded runtime to flush them
+#[tokio::test(flavor = "multi_thread")]
+async fn test_otlp_export() {
+    let (endpoint, mut spans) = start_collector().await;
+
+    let mut config = get_config().unwrap();
+    config.logging.format = LogFormat::Json;
+    config.logging.otlp_endpoint = Some(endpoint);
+    // Requests are handled on other threads, so the subscriber needs to be the global default
+    let telemetry = telemetry::init(&config.logging).unwrap();
+
+    let server = axum_test::TestServer::new(
+        get_app(&config).into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .unwrap();
+    server
+        .get(&format!("{WONDERS_ROUTE}/name/alhambra"))
+        .await
+        .assert_status_ok();
+    server.get(HEALTH_ROUTE).await.assert_status_ok();
+    server.get(GRAPHQL_ROUTE).await.assert_status_ok();
+
+    // Dropping the guard exports the remaining spans
+    tokio::task::spawn_blocking(move || drop(telemetry))
+        .await
+        .unwrap();
+
+    // Spans from other crates may also be exported
+    let mut requests = Vec::new();
+    while requests.len() < 3 {
+        let span = spans.recv().await.unwrap();
+        if span.name == "request" {
+            requests.push(span);
+        }
+    }
+    let matched_paths: Vec<_> = requests
+        .iter()
+        .map(|span| attribute(span, "matched_path"))
+        .collect();
+    assert_eq!(
+        matched_paths,
+        [
+            format!("{WONDERS_ROUTE}/name/:name"),
+            HEALTH_ROUTE.to_string(),
+            GRAPHQL_ROUTE.to_string()
+        ]
+        .map(|path| Some(Value::StringValue(path)))
+    );
+
+    let span = &requests[0];
+    assert_eq!(attribute(span, "status"), Some(Value::IntValue(200)));
+    assert!(matches!(
+        attribute(span, "latency_ms"),
+        Some(Value::DoubleValue(latency)) if latency > 0.0
+    ));
+}
@@ -0,0 +1,58 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_rustls::{
+    rustls::{
+        pki_types::{pem::PemObject, CertificateDer, ServerName},
+        ClientConfig, RootCertStore,
+    },
+    TlsConnector,
+};
+use world_wonders_api::{
+    config::{get_config, TlsConfig},
+    get_app, tls, HEALTH_ROUTE,
+};
+
+#[tokio::test]
+async fn test_tls() {
+    let tls_config = TlsConfig {
+        cert_path: "tests/fixtures/cert.pem".to_string(),
+        key_path: "tests/fixtures/key.pem".to_string(),
+        redirect_http_port: None,
+    };
+    let rustls_config = tls::load(&tls_config).await.unwrap();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = get_app(&get_config().unwrap()).into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(axum_server::from_tcp_rustls(listener, rustls_config).serve(app));
+
+    // Client trusting the self-signed test certificate
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(&tls_config.cert_path).unwrap())
+        .unwrap();
+    let connector = TlsConnector::from(Arc::new(
+        ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    ));
+
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let mut stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    stream
+        .write_all(
+            format!("GET {HEALTH_ROUTE} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("Healthy!"));
+}